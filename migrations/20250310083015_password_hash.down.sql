@@ -0,0 +1,8 @@
+-- Add down migration script here
+
+-- Rows that were already rehashed can't be turned back into plaintext.
+update users
+set password_hash = substr(password_hash, length('$plain$') + 1)
+where password_hash like '$plain$%';
+
+alter table users rename column password_hash to password;
@@ -0,0 +1,9 @@
+-- Add up migration script here
+
+alter table users rename column password to password_hash;
+
+-- Existing rows hold plaintext passwords which can't be hashed from SQL.
+-- Tag them so login can still verify them and rehash them with Argon2id.
+update users
+set password_hash = '$plain$' || password_hash
+where password_hash not like '$argon2%';
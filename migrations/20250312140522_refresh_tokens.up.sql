@@ -0,0 +1,13 @@
+-- Add up migration script here
+
+create table refresh_tokens (
+    token_hash text unique primary key not null,
+    family text not null,
+    username text not null references users (username) on delete cascade,
+    created_at integer not null,
+    expires_at integer not null,
+    used_at integer,
+    revoked_at integer
+);
+
+create index refresh_tokens_family on refresh_tokens (family);
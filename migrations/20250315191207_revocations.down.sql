@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+drop table revoked_users;
+drop table revoked_tokens;
@@ -0,0 +1,14 @@
+-- Add up migration script here
+
+create table revoked_tokens (
+    jti text unique primary key not null,
+    username text not null,
+    expires_at integer not null
+);
+
+-- Rows outlive the account, so the tokens of a deleted user stay revoked
+-- even when the name is registered again.
+create table revoked_users (
+    username text unique primary key not null,
+    revoked_before integer not null
+);
@@ -0,0 +1,5 @@
+-- Add up migration script here
+
+alter table users
+add column role text not null default 'member'
+check (role in ('admin', 'member', 'viewer'));
@@ -0,0 +1,10 @@
+-- Add up migration script here
+
+create table sessions (
+    id_hash text unique primary key not null,
+    username text not null references users (username) on delete cascade,
+    created_at integer not null,
+    expires_at integer not null
+);
+
+create index sessions_username on sessions (username);
@@ -0,0 +1,7 @@
+-- Add down migration script here
+
+drop table password_resets;
+
+drop index users_email;
+
+alter table users drop column email;
@@ -0,0 +1,13 @@
+-- Add up migration script here
+
+alter table users add column email text;
+
+create unique index users_email on users (email);
+
+create table password_resets (
+    token_hash text unique primary key not null,
+    username text not null references users (username) on delete cascade,
+    created_at integer not null,
+    expires_at integer not null,
+    used_at integer
+);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+drop table recovery_codes;
+drop table user_totp;
@@ -0,0 +1,16 @@
+-- Add up migration script here
+
+create table user_totp (
+    username text unique primary key not null references users (username) on delete cascade,
+    secret text not null,
+    confirmed_at integer,
+    last_used_step integer not null default 0
+);
+
+create table recovery_codes (
+    code_hash text unique primary key not null,
+    username text not null references users (username) on delete cascade,
+    used_at integer
+);
+
+create index recovery_codes_username on recovery_codes (username);
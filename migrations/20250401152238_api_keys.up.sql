@@ -0,0 +1,17 @@
+-- Add up migration script here
+
+create table api_keys (
+    id integer primary key autoincrement not null,
+    username text not null references users (username) on delete cascade,
+    name text not null,
+    prefix text not null,
+    key_hash text unique not null,
+    scopes text not null default '',
+    created_at integer not null,
+    expires_at integer,
+    last_used_at integer,
+    last_used_ip text,
+    revoked_at integer
+);
+
+create index api_keys_username on api_keys (username);
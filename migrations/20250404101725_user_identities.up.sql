@@ -0,0 +1,13 @@
+-- Add up migration script here
+
+create table user_identities (
+    issuer text not null,
+    subject text not null,
+    username text not null references users (username) on delete cascade,
+    email text,
+    created_at integer not null,
+    last_login_at integer not null,
+    primary key (issuer, subject)
+);
+
+create index user_identities_username on user_identities (username);
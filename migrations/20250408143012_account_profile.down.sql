@@ -0,0 +1,6 @@
+-- Add down migration script here
+
+alter table users drop column password_set;
+alter table users drop column timezone;
+alter table users drop column avatar_url;
+alter table users drop column display_name;
@@ -0,0 +1,8 @@
+-- Add up migration script here
+
+alter table users add column display_name text;
+alter table users add column avatar_url text;
+alter table users add column timezone text;
+-- Unset for accounts created through a login provider until a password is
+-- chosen through a reset.
+alter table users add column password_set integer not null default 1;
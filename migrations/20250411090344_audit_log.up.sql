@@ -0,0 +1,17 @@
+-- Add up migration script here
+
+create table audit_log (
+    id integer primary key autoincrement not null,
+    event text not null,
+    actor text,
+    target text,
+    ip text,
+    user_agent text,
+    details text,
+    created_at integer not null
+);
+
+create index audit_log_event on audit_log (event);
+create index audit_log_actor on audit_log (actor);
+create index audit_log_target on audit_log (target);
+create index audit_log_created_at on audit_log (created_at);
@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+drop index audit_log_impersonator;
+
+alter table audit_log drop column impersonator;
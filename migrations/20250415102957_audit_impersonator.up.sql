@@ -0,0 +1,5 @@
+-- Add up migration script here
+
+alter table audit_log add column impersonator text;
+
+create index audit_log_impersonator on audit_log (impersonator);
@@ -0,0 +1,13 @@
+-- Add down migration script here
+
+drop index tickets_status;
+drop index tickets_assignee;
+drop index tickets_reporter;
+
+alter table tickets drop column updated_at;
+alter table tickets drop column created_at;
+alter table tickets drop column assignee;
+alter table tickets drop column reporter;
+alter table tickets drop column priority;
+alter table tickets drop column status;
+alter table tickets drop column description;
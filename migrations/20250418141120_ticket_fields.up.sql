@@ -0,0 +1,19 @@
+-- Add up migration script here
+
+alter table tickets add column description text not null default '';
+alter table tickets add column status text not null default 'open'
+    check (status in ('open', 'in_progress', 'resolved', 'closed'));
+alter table tickets add column priority text not null default 'medium'
+    check (priority in ('low', 'medium', 'high', 'urgent'));
+alter table tickets add column reporter text references users (username) on delete set null;
+alter table tickets add column assignee text references users (username) on delete set null;
+alter table tickets add column created_at integer not null default 0;
+alter table tickets add column updated_at integer not null default 0;
+
+update tickets
+set created_at = cast(strftime('%s', 'now') as integer),
+    updated_at = cast(strftime('%s', 'now') as integer);
+
+create index tickets_reporter on tickets (reporter);
+create index tickets_assignee on tickets (assignee);
+create index tickets_status on tickets (status);
@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+drop index tickets_priority;
+drop index tickets_updated_at;
+drop index tickets_created_at;
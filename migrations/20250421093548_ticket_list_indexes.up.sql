@@ -0,0 +1,5 @@
+-- Add up migration script here
+
+create index tickets_created_at on tickets (created_at);
+create index tickets_updated_at on tickets (updated_at);
+create index tickets_priority on tickets (priority);
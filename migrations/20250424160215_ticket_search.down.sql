@@ -0,0 +1,7 @@
+-- Add down migration script here
+
+drop trigger tickets_fts_update;
+drop trigger tickets_fts_delete;
+drop trigger tickets_fts_insert;
+
+drop table tickets_fts;
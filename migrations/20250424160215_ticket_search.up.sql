@@ -0,0 +1,31 @@
+-- Add up migration script here
+
+create virtual table tickets_fts using fts5 (
+    title,
+    description,
+    content = 'tickets',
+    content_rowid = 'id',
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+create trigger tickets_fts_insert after insert on tickets
+begin
+    insert into tickets_fts (rowid, title, description)
+    values (new.id, new.title, new.description);
+end;
+
+create trigger tickets_fts_delete after delete on tickets
+begin
+    insert into tickets_fts (tickets_fts, rowid, title, description)
+    values ('delete', old.id, old.title, old.description);
+end;
+
+create trigger tickets_fts_update after update of title, description on tickets
+begin
+    insert into tickets_fts (tickets_fts, rowid, title, description)
+    values ('delete', old.id, old.title, old.description);
+    insert into tickets_fts (rowid, title, description)
+    values (new.id, new.title, new.description);
+end;
+
+insert into tickets_fts (tickets_fts) values ('rebuild');
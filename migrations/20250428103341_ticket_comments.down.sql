@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+drop table ticket_comment_edits;
+drop table ticket_comments;
@@ -0,0 +1,25 @@
+-- Add up migration script here
+
+create table ticket_comments (
+    id integer primary key autoincrement,
+    ticket_id integer not null references tickets (id) on delete cascade,
+    parent_id integer references ticket_comments (id) on delete cascade,
+    author text references users (username) on delete set null,
+    body text not null,
+    created_at integer not null,
+    updated_at integer not null,
+    deleted_at integer
+);
+
+create index ticket_comments_ticket on ticket_comments (ticket_id, id);
+create index ticket_comments_parent on ticket_comments (parent_id);
+
+create table ticket_comment_edits (
+    id integer primary key autoincrement,
+    comment_id integer not null references ticket_comments (id) on delete cascade,
+    body text not null,
+    edited_by text references users (username) on delete set null,
+    edited_at integer not null
+);
+
+create index ticket_comment_edits_comment on ticket_comment_edits (comment_id);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+drop table ticket_labels;
+drop table labels;
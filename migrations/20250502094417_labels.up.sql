@@ -0,0 +1,17 @@
+-- Add up migration script here
+
+create table labels (
+    id integer primary key autoincrement,
+    name text not null unique collate nocase,
+    color text not null,
+    description text not null default '',
+    created_at integer not null
+);
+
+create table ticket_labels (
+    ticket_id integer not null references tickets (id) on delete cascade,
+    label_id integer not null references labels (id) on delete cascade,
+    primary key (ticket_id, label_id)
+);
+
+create index ticket_labels_label on ticket_labels (label_id);
@@ -0,0 +1,18 @@
+-- Add up migration script here
+
+create table ticket_history (
+    id integer primary key autoincrement,
+    ticket_id integer not null references tickets (id) on delete cascade,
+    action text not null,
+    field text,
+    old_value text,
+    new_value text,
+    comment_id integer references ticket_comments (id) on delete set null,
+    actor text references users (username) on delete set null,
+    impersonator text references users (username) on delete set null,
+    created_at integer not null
+);
+
+create index ticket_history_ticket on ticket_history (ticket_id, id);
+create index ticket_history_actor on ticket_history (actor, id);
+create index ticket_history_comment on ticket_history (comment_id);
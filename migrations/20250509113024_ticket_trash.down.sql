@@ -0,0 +1,6 @@
+-- Add down migration script here
+
+drop index tickets_deleted_at;
+
+alter table tickets drop column deleted_by;
+alter table tickets drop column deleted_at;
@@ -0,0 +1,6 @@
+-- Add up migration script here
+
+alter table tickets add column deleted_at integer;
+alter table tickets add column deleted_by text references users (username) on delete set null;
+
+create index tickets_deleted_at on tickets (deleted_at);
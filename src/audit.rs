@@ -0,0 +1,118 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{Pool, Sqlite};
+use tracing::error;
+
+use crate::middleware::{client::ClientInfo, jwt::Claims};
+
+/// Security relevant events, stored by name so old entries stay readable
+/// when variants are added.
+#[derive(Serialize, Deserialize, sqlx::Type, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum Event {
+    LoginSucceeded,
+    LoginFailed,
+    LoginBlocked,
+    MfaFailed,
+    Logout,
+    LogoutAll,
+    TokenRefreshed,
+    TokenReused,
+    PasswordChanged,
+    PasswordResetRequested,
+    PasswordReset,
+    TotpEnabled,
+    TotpDisabled,
+    ApiKeyCreated,
+    ApiKeyRevoked,
+    AccountDeleted,
+    RoleChanged,
+    AccountUnlocked,
+    TicketDeleted,
+    TicketRestored,
+    TicketsPurged,
+    ChatUserDeleted,
+    ChatRoomDeleted,
+    ImpersonationStarted,
+    IdentityLinked,
+}
+
+/// One audit log entry, e.g.
+/// `Entry::new(Event::RoleChanged, &client).by(&claims).target(&username)`.
+pub struct Entry {
+    event: Event,
+    actor: Option<String>,
+    impersonator: Option<String>,
+    target: Option<String>,
+    client: ClientInfo,
+    details: Option<Value>,
+}
+
+impl Entry {
+    pub fn new(event: Event, client: &ClientInfo) -> Self {
+        Self {
+            event,
+            actor: None,
+            impersonator: None,
+            target: None,
+            client: client.clone(),
+            details: None,
+        }
+    }
+
+    /// Who did it, for failed logins the username that was tried.
+    pub fn actor(mut self, actor: &str) -> Self {
+        self.actor = Some(actor.to_string());
+        self
+    }
+
+    /// The user of the token, and the admin behind it when impersonating.
+    pub fn by(mut self, claims: &Claims) -> Self {
+        self.actor = Some(claims.sub.clone());
+        self.impersonator = claims.actor().map(str::to_string);
+        self
+    }
+
+    /// Who or what it was done to, if not the actor itself.
+    pub fn target(mut self, target: impl ToString) -> Self {
+        self.target = Some(target.to_string());
+        self
+    }
+
+    pub fn details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    /// A failed write is logged rather than returned, so the audit log can't
+    /// turn into a way of breaking logins.
+    pub async fn record(self, pool: &Pool<Sqlite>) {
+        let created_at = Utc::now().timestamp();
+        let details = self.details.map(|details| details.to_string());
+
+        let result = sqlx::query!(
+            r#"
+            insert into audit_log (
+                event, actor, impersonator, target, ip, user_agent, details, created_at
+            )
+            values (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            self.event,
+            self.actor,
+            self.impersonator,
+            self.target,
+            self.client.ip,
+            self.client.user_agent,
+            details,
+            created_at,
+        )
+        .execute(pool)
+        .await;
+
+        if let Err(e) = result {
+            error!("[{:^12}] ┃ record {:?}: {e:?}", "Audit", self.event);
+        }
+    }
+}
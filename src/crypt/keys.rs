@@ -0,0 +1,195 @@
+use std::{collections::HashMap, fs, str::FromStr, sync::LazyLock};
+
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use rand::{RngCore, rngs::OsRng};
+use serde::{Serialize, de::DeserializeOwned};
+use tracing::warn;
+
+use crate::{
+    config::{JwtConfig, config},
+    error::{AuthError, Result},
+};
+
+static KEYS: LazyLock<Keys> = LazyLock::new(|| Keys::from_config(&config().jwt));
+
+pub fn keys() -> &'static Keys {
+    &KEYS
+}
+
+/// Signs tokens with the current key and verifies them with any key that is
+/// still configured, selected through the `kid` header. Rotating is done by
+/// moving the old key to `JWT_VERIFY_KEYS` until its last token has expired.
+pub struct Keys {
+    kid: String,
+    algorithm: Algorithm,
+    encoding: EncodingKey,
+    decoding: HashMap<String, (Algorithm, DecodingKey)>,
+}
+
+impl Keys {
+    pub fn encode<T: Serialize>(&self, claims: &T) -> Result<String> {
+        let header = Header {
+            kid: Some(self.kid.clone()),
+            ..Header::new(self.algorithm)
+        };
+
+        jsonwebtoken::encode(&header, claims, &self.encoding)
+            .map_err(|_| AuthError::InvalidToken.into())
+    }
+
+    /// Tokens carrying an `aud` claim are rejected here, they can only be
+    /// read through [`Keys::decode_audience`].
+    pub fn decode<T: DeserializeOwned>(&self, token: &str) -> Result<T> {
+        self.decode_with(token, |_| {})
+    }
+
+    pub fn decode_audience<T: DeserializeOwned>(&self, token: &str, audience: &str) -> Result<T> {
+        self.decode_with(token, |validation| validation.set_audience(&[audience]))
+    }
+
+    fn decode_with<T: DeserializeOwned>(
+        &self,
+        token: &str,
+        configure: impl FnOnce(&mut Validation),
+    ) -> Result<T> {
+        let header = jsonwebtoken::decode_header(token).map_err(|_| AuthError::InvalidToken)?;
+
+        let (algorithm, key) = header
+            .kid
+            .and_then(|kid| self.decoding.get(&kid))
+            .ok_or(AuthError::InvalidToken)?;
+
+        let mut validation = Validation::new(*algorithm);
+        configure(&mut validation);
+
+        let token_data = jsonwebtoken::decode::<T>(token, key, &validation)
+            .map_err(|_| AuthError::InvalidToken)?;
+
+        Ok(token_data.claims)
+    }
+}
+
+impl Keys {
+    fn from_config(jwt: &JwtConfig) -> Self {
+        let algorithm = parse_algorithm(&jwt.algorithm);
+
+        let (encoding, decoding) = match KeyKind::of(algorithm) {
+            KeyKind::Hmac => {
+                let secret = jwt
+                    .secret
+                    .clone()
+                    .map(String::into_bytes)
+                    .unwrap_or_else(|| {
+                        warn!(
+                            "[{:^12}] ━ JWT_SECRET not set, tokens won't survive a restart",
+                            "Keys"
+                        );
+
+                        let mut secret = vec![0; 32];
+                        OsRng.fill_bytes(&mut secret);
+                        secret
+                    });
+
+                (
+                    EncodingKey::from_secret(&secret),
+                    DecodingKey::from_secret(&secret),
+                )
+            }
+
+            kind => {
+                let private_key = read_pem(jwt.private_key.as_deref(), "JWT_PRIVATE_KEY_FILE");
+                let public_key = read_pem(jwt.public_key.as_deref(), "JWT_PUBLIC_KEY_FILE");
+
+                (
+                    kind.encoding_key(&private_key),
+                    kind.decoding_key(&public_key),
+                )
+            }
+        };
+
+        let mut decoding_keys = HashMap::from([(jwt.kid.clone(), (algorithm, decoding))]);
+
+        for entry in &jwt.verify_keys {
+            let (kid, key) = parse_verify_key(entry);
+
+            if decoding_keys.insert(kid.clone(), key).is_some() {
+                panic!("Duplicate JWT key id {kid:?}");
+            }
+        }
+
+        Self {
+            kid: jwt.kid.clone(),
+            algorithm,
+            encoding,
+            decoding: decoding_keys,
+        }
+    }
+}
+
+enum KeyKind {
+    Hmac,
+    Rsa,
+    Ec,
+    Ed,
+}
+
+impl KeyKind {
+    fn of(algorithm: Algorithm) -> Self {
+        match algorithm {
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => Self::Hmac,
+            Algorithm::ES256 | Algorithm::ES384 => Self::Ec,
+            Algorithm::EdDSA => Self::Ed,
+            _ => Self::Rsa,
+        }
+    }
+
+    fn encoding_key(&self, pem: &[u8]) -> EncodingKey {
+        match self {
+            Self::Hmac => Ok(EncodingKey::from_secret(pem)),
+            Self::Rsa => EncodingKey::from_rsa_pem(pem),
+            Self::Ec => EncodingKey::from_ec_pem(pem),
+            Self::Ed => EncodingKey::from_ed_pem(pem),
+        }
+        .unwrap_or_else(|e| panic!("Invalid JWT private key: {e}"))
+    }
+
+    fn decoding_key(&self, pem: &[u8]) -> DecodingKey {
+        match self {
+            Self::Hmac => Ok(DecodingKey::from_secret(pem)),
+            Self::Rsa => DecodingKey::from_rsa_pem(pem),
+            Self::Ec => DecodingKey::from_ec_pem(pem),
+            Self::Ed => DecodingKey::from_ed_pem(pem),
+        }
+        .unwrap_or_else(|e| panic!("Invalid JWT public key: {e}"))
+    }
+}
+
+fn parse_algorithm(algorithm: &str) -> Algorithm {
+    Algorithm::from_str(algorithm)
+        .unwrap_or_else(|_| panic!("Unsupported JWT algorithm {algorithm:?}"))
+}
+
+fn read_pem(path: Option<&str>, name: &str) -> Vec<u8> {
+    let path = path.unwrap_or_else(|| panic!("{name} must be set for asymmetric JWT keys"));
+
+    fs::read(path).unwrap_or_else(|e| panic!("Failed to read {name} {path:?}: {e}"))
+}
+
+/// Parses `kid=ALG:value` where value is the secret for `HS*` algorithms
+/// and the path of a public key PEM file otherwise.
+fn parse_verify_key(entry: &str) -> (String, (Algorithm, DecodingKey)) {
+    let Some((kid, rest)) = entry.split_once('=') else {
+        panic!("Invalid JWT_VERIFY_KEYS entry {entry:?}, expected kid=ALG:value");
+    };
+    let Some((algorithm, value)) = rest.split_once(':') else {
+        panic!("Invalid JWT_VERIFY_KEYS entry {entry:?}, expected kid=ALG:value");
+    };
+
+    let algorithm = parse_algorithm(algorithm);
+    let key = match KeyKind::of(algorithm) {
+        KeyKind::Hmac => DecodingKey::from_secret(value.as_bytes()),
+        kind => kind.decoding_key(&read_pem(Some(value), "JWT_VERIFY_KEYS")),
+    };
+
+    (kid.to_string(), (algorithm, key))
+}
@@ -0,0 +1,4 @@
+pub mod keys;
+pub mod password;
+pub mod token;
+pub mod totp;
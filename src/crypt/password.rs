@@ -0,0 +1,99 @@
+use std::sync::LazyLock;
+
+use argon2::{
+    Algorithm, Argon2, Params, PasswordHash, PasswordHasher, PasswordVerifier, Version,
+    password_hash::{self, SaltString},
+};
+use rand::rngs::OsRng;
+use subtle::ConstantTimeEq;
+use tokio::task;
+
+use crate::{
+    config::config,
+    error::{CryptError, Result},
+};
+
+/// Marks rows that were stored as plaintext before hashing was introduced,
+/// see the `password_hash` migration. They are upgraded on the next login.
+const LEGACY_PREFIX: &str = "$plain$";
+
+static DUMMY_HASH: LazyLock<String> =
+    LazyLock::new(|| hash_blocking("dummy password").expect("Failed to hash dummy password"));
+
+pub async fn hash(password: String) -> Result<String> {
+    task::spawn_blocking(move || hash_blocking(&password))
+        .await
+        .map_err(|_| CryptError::HashFailed)?
+}
+
+pub async fn verify(password: String, hash: String) -> Result<bool> {
+    task::spawn_blocking(move || verify_blocking(&password, &hash))
+        .await
+        .map_err(|_| CryptError::HashFailed)?
+}
+
+/// Burns the same amount of time as a real verification, so unknown
+/// usernames can't be told apart from wrong passwords.
+pub async fn verify_dummy(password: String) -> Result<bool> {
+    verify(password, DUMMY_HASH.clone()).await
+}
+
+pub fn needs_rehash(hash: &str) -> bool {
+    let Ok(parsed) = PasswordHash::new(hash) else {
+        return true;
+    };
+
+    if parsed.algorithm != Algorithm::Argon2id.ident()
+        || parsed.version != Some(Version::V0x13.into())
+    {
+        return true;
+    }
+
+    let Ok(params) = Params::try_from(&parsed) else {
+        return true;
+    };
+
+    let password = &config().password;
+
+    params.m_cost() < password.memory_cost
+        || params.t_cost() < password.time_cost
+        || params.p_cost() < password.parallelism
+}
+
+fn hash_blocking(password: &str) -> Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+
+    let hash = argon2()?
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|_| CryptError::HashFailed)?;
+
+    Ok(hash.to_string())
+}
+
+fn verify_blocking(password: &str, hash: &str) -> Result<bool> {
+    if let Some(plain) = hash.strip_prefix(LEGACY_PREFIX) {
+        return Ok(password.as_bytes().ct_eq(plain.as_bytes()).into());
+    }
+
+    let parsed = PasswordHash::new(hash).map_err(|_| CryptError::InvalidHash)?;
+
+    match Argon2::default().verify_password(password.as_bytes(), &parsed) {
+        Ok(()) => Ok(true),
+        Err(password_hash::Error::Password) => Ok(false),
+        Err(_) => Err(CryptError::InvalidHash.into()),
+    }
+}
+
+fn argon2() -> Result<Argon2<'static>> {
+    let password = &config().password;
+
+    let params = Params::new(
+        password.memory_cost,
+        password.time_cost,
+        password.parallelism,
+        None,
+    )
+    .map_err(|_| CryptError::HashFailed)?;
+
+    Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+}
@@ -0,0 +1,17 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use rand::{RngCore, rngs::OsRng};
+use sha2::{Digest, Sha256};
+
+/// Random, URL safe token to be handed out to clients. Only its hash is stored.
+pub fn generate() -> String {
+    let mut bytes = [0; 32];
+    OsRng.fill_bytes(&mut bytes);
+
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Tokens carry enough entropy that a plain SHA-256 is sufficient,
+/// unlike passwords which go through `crypt::password`.
+pub fn hash(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
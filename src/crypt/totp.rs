@@ -0,0 +1,90 @@
+use chrono::Utc;
+use rand::{RngCore, rngs::OsRng};
+use subtle::ConstantTimeEq;
+use totp_rs::{Algorithm, Secret, TOTP};
+
+use crate::{
+    config::config,
+    error::{CryptError, Result},
+};
+
+const DIGITS: usize = 6;
+const STEP: u64 = 30;
+const RECOVERY_CODES: usize = 10;
+
+/// Returns a new base32 encoded secret.
+pub fn generate_secret(username: &str) -> Result<String> {
+    let mut secret = vec![0; 20];
+    OsRng.fill_bytes(&mut secret);
+
+    Ok(totp(secret, username)?.get_secret_base32())
+}
+
+/// `otpauth://` URI to be shown as QR code by clients.
+pub fn uri(secret: &str, username: &str) -> Result<String> {
+    Ok(totp(decode(secret)?, username)?.get_url())
+}
+
+/// Accepts codes from the previous, current and next time step to allow for
+/// clock drift, and returns the matched step so callers can refuse replays.
+pub fn verify(secret: &str, username: &str, code: &str) -> Result<Option<i64>> {
+    let totp = totp(decode(secret)?, username)?;
+    let current = Utc::now().timestamp() as u64 / STEP;
+
+    let step = [current - 1, current, current + 1]
+        .into_iter()
+        .find(|step| {
+            totp.generate(step * STEP)
+                .as_bytes()
+                .ct_eq(code.trim().as_bytes())
+                .into()
+        });
+
+    Ok(step.map(|step| step as i64))
+}
+
+/// One-time codes for when the authenticator is lost, formatted as four
+/// groups of eight hex digits for readability. 128 bits each, so that like
+/// other tokens they can be stored as a plain `crypt::token::hash`.
+pub fn recovery_codes() -> Vec<String> {
+    (0..RECOVERY_CODES)
+        .map(|_| {
+            let mut bytes = [0u8; 16];
+            OsRng.fill_bytes(&mut bytes);
+
+            bytes
+                .chunks(4)
+                .map(|group| group.iter().map(|b| format!("{b:02x}")).collect::<String>())
+                .collect::<Vec<_>>()
+                .join("-")
+        })
+        .collect()
+}
+
+/// Recovery codes are compared without dashes or case, so users can type
+/// them however they like.
+pub fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn totp(secret: Vec<u8>, username: &str) -> Result<TOTP> {
+    TOTP::new(
+        Algorithm::SHA1,
+        DIGITS,
+        0,
+        STEP,
+        secret,
+        Some(config().mfa.issuer.clone()),
+        username.to_string(),
+    )
+    .map_err(|_| CryptError::InvalidTotpSecret.into())
+}
+
+fn decode(secret: &str) -> Result<Vec<u8>> {
+    Secret::Encoded(secret.to_string())
+        .to_bytes()
+        .map_err(|_| CryptError::InvalidTotpSecret.into())
+}
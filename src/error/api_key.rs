@@ -0,0 +1,18 @@
+use axum::http::StatusCode;
+use thiserror::Error;
+
+use super::ErrorStatusCode;
+
+#[derive(Debug, Error)]
+pub enum ApiKeyError {
+    #[error("API key with id {0} not found")]
+    NotFound(i64),
+}
+
+impl ErrorStatusCode for ApiKeyError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+        }
+    }
+}
@@ -8,17 +8,74 @@ pub enum AuthError {
     #[error("Invalid token")]
     InvalidToken,
 
+    #[error("Token has been revoked")]
+    RevokedToken,
+
+    #[error("Invalid refresh token")]
+    InvalidRefreshToken,
+
+    #[error("Invalid or expired password reset token")]
+    InvalidResetToken,
+
+    #[error("Invalid authentication code")]
+    InvalidMfaCode,
+
+    #[error("Two-factor authentication is already enabled")]
+    MfaAlreadyEnabled,
+
+    #[error("Two-factor authentication is not set up")]
+    MfaNotEnrolled,
+
+    #[error("Token is missing scope {0}")]
+    MissingScope(&'static str),
+
+    #[error("Invalid API key")]
+    InvalidApiKey,
+
+    #[error("Missing or invalid CSRF token")]
+    InvalidCsrfToken,
+
     #[error("Invalid cookie")]
     InvalidCookie,
 
     #[error("Invalid username or password")]
     WrongCredentials,
+
+    #[error("Insufficient permissions")]
+    Forbidden,
+
+    #[error("Not allowed while impersonating")]
+    Impersonating,
+
+    #[error("Too many login attempts, retry in {retry_after} seconds")]
+    TooManyAttempts { retry_after: i64 },
+
+    #[error("Account locked, retry in {retry_after} seconds")]
+    AccountLocked { retry_after: i64 },
+}
+
+impl AuthError {
+    pub fn retry_after(&self) -> Option<i64> {
+        match self {
+            Self::TooManyAttempts { retry_after } | Self::AccountLocked { retry_after } => {
+                Some(*retry_after)
+            }
+            _ => None,
+        }
+    }
 }
 
-#[allow(clippy::match_single_binding)]
 impl ErrorStatusCode for AuthError {
     fn status_code(&self) -> StatusCode {
         match self {
+            Self::Forbidden
+            | Self::MissingScope(_)
+            | Self::InvalidCsrfToken
+            | Self::Impersonating => StatusCode::FORBIDDEN,
+            Self::InvalidResetToken | Self::MfaNotEnrolled => StatusCode::BAD_REQUEST,
+            Self::MfaAlreadyEnabled => StatusCode::CONFLICT,
+            Self::TooManyAttempts { .. } => StatusCode::TOO_MANY_REQUESTS,
+            Self::AccountLocked { .. } => StatusCode::LOCKED,
             _ => StatusCode::UNAUTHORIZED,
         }
     }
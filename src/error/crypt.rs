@@ -0,0 +1,25 @@
+use axum::http::StatusCode;
+use thiserror::Error;
+
+use super::ErrorStatusCode;
+
+#[derive(Debug, Error)]
+pub enum CryptError {
+    #[error("Failed to hash password")]
+    HashFailed,
+
+    #[error("Stored password hash is malformed")]
+    InvalidHash,
+
+    #[error("Stored TOTP secret is malformed")]
+    InvalidTotpSecret,
+}
+
+#[allow(clippy::match_single_binding)]
+impl ErrorStatusCode for CryptError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
@@ -11,6 +11,9 @@ pub enum DatabaseError {
     #[error("Database select failed")]
     SelectFailed,
 
+    #[error("Database update failed")]
+    UpdateFailed,
+
     #[error("Database delete failed")]
     DeleteFailed,
 }
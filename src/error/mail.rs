@@ -0,0 +1,22 @@
+use axum::http::StatusCode;
+use thiserror::Error;
+
+use super::ErrorStatusCode;
+
+#[derive(Debug, Error)]
+pub enum MailError {
+    #[error("Invalid mail address {0}")]
+    InvalidAddress(String),
+
+    #[error("Failed to send mail")]
+    SendFailed,
+}
+
+impl ErrorStatusCode for MailError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::InvalidAddress(_) => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
@@ -1,18 +1,32 @@
+mod api_key;
 mod auth;
+mod crypt;
 mod database;
+mod mail;
+mod oidc;
 mod room;
 mod ticket;
+mod user;
+mod validation;
 
+pub use api_key::ApiKeyError;
 pub use auth::AuthError;
+pub use crypt::CryptError;
 pub use database::DatabaseError;
+pub use mail::MailError;
+pub use oidc::OidcError;
 pub use room::RoomError;
 pub use ticket::TicketError;
+pub use user::UserError;
+pub use validation::ValidationError;
 pub type Result<T> = std::result::Result<T, Error>;
 
 use axum::{
-    http::StatusCode,
+    Json,
+    http::{HeaderValue, StatusCode, header},
     response::{IntoResponse, Response},
 };
+use serde_json::json;
 use thiserror::Error;
 use tracing::error;
 
@@ -30,6 +44,24 @@ pub enum Error {
     #[error(transparent)]
     Room(#[from] RoomError),
 
+    #[error(transparent)]
+    Crypt(#[from] CryptError),
+
+    #[error(transparent)]
+    User(#[from] UserError),
+
+    #[error(transparent)]
+    Mail(#[from] MailError),
+
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
+
+    #[error(transparent)]
+    ApiKey(#[from] ApiKeyError),
+
+    #[error(transparent)]
+    Oidc(#[from] OidcError),
+
     #[error("Unknown error")]
     Unknown,
 }
@@ -38,15 +70,39 @@ impl IntoResponse for Error {
     fn into_response(self) -> Response {
         error!("[{:^12}] ┃ {self:?}", "Error");
 
+        let retry_after = match &self {
+            Error::Auth(e) => e.retry_after(),
+            _ => None,
+        };
+
+        if let Error::Validation(e) = self {
+            let body = json!({ "error": e.to_string(), "fields": e.fields });
+            return (e.status_code(), Json(body)).into_response();
+        }
+
         let (status, message) = match self {
             Error::Auth(e) => (e.status_code(), e.to_string()),
             Error::Ticket(e) => (e.status_code(), e.to_string()),
             Error::Database(e) => (e.status_code(), e.to_string()),
             Error::Room(e) => (e.status_code(), e.to_string()),
+            Error::Crypt(e) => (e.status_code(), e.to_string()),
+            Error::User(e) => (e.status_code(), e.to_string()),
+            Error::Mail(e) => (e.status_code(), e.to_string()),
+            Error::Validation(e) => (e.status_code(), e.to_string()),
+            Error::ApiKey(e) => (e.status_code(), e.to_string()),
+            Error::Oidc(e) => (e.status_code(), e.to_string()),
             Error::Unknown => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
         };
 
-        (status, message).into_response()
+        let mut response = (status, message).into_response();
+
+        if let Some(seconds) = retry_after {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+        }
+
+        response
     }
 }
 
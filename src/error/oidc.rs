@@ -0,0 +1,40 @@
+use axum::http::StatusCode;
+use thiserror::Error;
+
+use super::ErrorStatusCode;
+
+#[derive(Debug, Error)]
+pub enum OidcError {
+    #[error("Single sign-on is not configured")]
+    NotConfigured,
+
+    #[error("Failed to reach the identity provider")]
+    ProviderUnavailable,
+
+    #[error("Unknown or expired login state")]
+    InvalidState,
+
+    #[error("Identity provider denied the login: {0}")]
+    Denied(String),
+
+    #[error("Failed to exchange the authorization code")]
+    ExchangeFailed,
+
+    #[error("Invalid ID token")]
+    InvalidIdToken,
+
+    #[error("An account with this email already exists, log in to link it")]
+    LinkRequired,
+}
+
+impl ErrorStatusCode for OidcError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::NotConfigured => StatusCode::NOT_FOUND,
+            Self::ProviderUnavailable | Self::ExchangeFailed => StatusCode::BAD_GATEWAY,
+            Self::InvalidState | Self::Denied(_) => StatusCode::BAD_REQUEST,
+            Self::InvalidIdToken => StatusCode::UNAUTHORIZED,
+            Self::LinkRequired => StatusCode::CONFLICT,
+        }
+    }
+}
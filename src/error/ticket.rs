@@ -5,18 +5,33 @@ use super::ErrorStatusCode;
 
 #[derive(Debug, Error)]
 pub enum TicketError {
-    #[error("Failed to create ticket")]
-    CreateFailed,
-
     #[error("Ticket with id {0} not found")]
     NotFound(i64),
+
+    #[error("Only the reporter, the assignee or an admin may modify ticket {0}")]
+    Forbidden(i64),
+
+    #[error("Comment with id {0} not found")]
+    CommentNotFound(i64),
+
+    #[error("Only the author may edit comment {0}")]
+    CommentForbidden(i64),
+
+    #[error("Label with id {0} not found")]
+    LabelNotFound(i64),
+
+    #[error("Label {0} already exists")]
+    LabelTaken(String),
 }
 
 impl ErrorStatusCode for TicketError {
     fn status_code(&self) -> StatusCode {
         match self {
-            Self::NotFound(_) => StatusCode::NOT_FOUND,
-            _ => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::NotFound(_) | Self::CommentNotFound(_) | Self::LabelNotFound(_) => {
+                StatusCode::NOT_FOUND
+            }
+            Self::LabelTaken(_) => StatusCode::CONFLICT,
+            Self::Forbidden(_) | Self::CommentForbidden(_) => StatusCode::FORBIDDEN,
         }
     }
 }
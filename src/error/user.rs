@@ -0,0 +1,22 @@
+use axum::http::StatusCode;
+use thiserror::Error;
+
+use super::ErrorStatusCode;
+
+#[derive(Debug, Error)]
+pub enum UserError {
+    #[error("User {0} not found")]
+    NotFound(String),
+
+    #[error("Email address is already in use")]
+    EmailTaken,
+}
+
+impl ErrorStatusCode for UserError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::EmailTaken => StatusCode::CONFLICT,
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use std::collections::BTreeMap;
+
+use axum::http::StatusCode;
+use serde::Serialize;
+use thiserror::Error;
+
+use super::ErrorStatusCode;
+
+/// Per field messages, rendered as JSON rather than plain text.
+#[derive(Debug, Error, Serialize, Default)]
+#[error("Validation failed")]
+pub struct ValidationError {
+    pub fields: BTreeMap<&'static str, Vec<String>>,
+}
+
+impl ValidationError {
+    pub fn add(&mut self, field: &'static str, message: impl Into<String>) {
+        self.fields.entry(field).or_default().push(message.into());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+}
+
+impl ErrorStatusCode for ValidationError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::UNPROCESSABLE_ENTITY
+    }
+}
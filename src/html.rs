@@ -0,0 +1,150 @@
+use pulldown_cmark::{CowStr, Event, Options, Parser, Tag, html};
+
+/// Escapes text for use in HTML element content and quoted attributes.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Renders untrusted markdown. Raw HTML is shown as text, and links and
+/// images pointing anywhere but http, https, mailto or relative URLs lose
+/// their target.
+pub fn markdown(source: &str) -> String {
+    let options =
+        Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TABLES | Options::ENABLE_TASKLISTS;
+
+    let events = Parser::new_ext(source, options).map(|event| match event {
+        Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+        Event::Start(Tag::Link {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Link {
+            link_type,
+            dest_url: safe_url(dest_url),
+            title,
+            id,
+        }),
+        Event::Start(Tag::Image {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Image {
+            link_type,
+            dest_url: safe_url(dest_url),
+            title,
+            id,
+        }),
+        event => event,
+    });
+
+    let mut rendered = String::new();
+    html::push_html(&mut rendered, events);
+
+    rendered
+}
+
+fn safe_url(url: CowStr<'_>) -> CowStr<'_> {
+    let scheme = url
+        .split_once(':')
+        .map(|(scheme, _)| scheme)
+        .filter(|scheme| !scheme.contains(['/', '?', '#']));
+
+    match scheme {
+        None => url,
+        Some(scheme) if ["http", "https", "mailto"].contains(&scheme.to_lowercase().as_str()) => {
+            url
+        }
+        Some(_) => CowStr::Borrowed(""),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::markdown;
+
+    #[test]
+    fn raw_html_is_text() {
+        let rendered = markdown("<script>alert(1)</script>\n\ninline <img src=x onerror=alert(1)>");
+
+        assert!(!rendered.contains("<script"));
+        assert!(!rendered.contains("<img"));
+        assert!(rendered.contains("&lt;script&gt;alert(1)&lt;/script&gt;"));
+    }
+
+    #[test]
+    fn unsafe_schemes_lose_their_target() {
+        for url in [
+            "javascript:alert(1)",
+            "JaVaScRiPt:alert(1)",
+            "data:text/html,x",
+        ] {
+            assert_eq!(
+                markdown(&format!("[x]({url})")),
+                "<p><a href=\"\">x</a></p>\n",
+                "{url}"
+            );
+            assert_eq!(
+                markdown(&format!("![x]({url})")),
+                "<p><img src=\"\" alt=\"x\" /></p>\n",
+                "{url}"
+            );
+        }
+    }
+
+    #[test]
+    fn autolinks_are_checked() {
+        assert_eq!(
+            markdown("<javascript:alert(1)>"),
+            "<p><a href=\"\">javascript:alert(1)</a></p>\n"
+        );
+        assert_eq!(
+            markdown("<https://example.com>"),
+            "<p><a href=\"https://example.com\">https://example.com</a></p>\n"
+        );
+    }
+
+    #[test]
+    fn entity_encoded_schemes_are_checked() {
+        for source in [
+            "[x](&#106;avascript:alert(1))",
+            "[x](javascript&#58;alert(1))",
+            "[x](&#x6A;&#x61;vascript:alert(1))",
+        ] {
+            assert_eq!(markdown(source), "<p><a href=\"\">x</a></p>\n", "{source}");
+        }
+    }
+
+    #[test]
+    fn safe_urls_are_kept() {
+        for url in [
+            "https://example.com/a?b#c",
+            "http://example.com",
+            "mailto:someone@example.com",
+            "/ticket/1",
+            "../docs/setup.md",
+            "#comments",
+            "page?at=12:30",
+        ] {
+            assert_eq!(
+                markdown(&format!("[x]({url})")),
+                format!("<p><a href=\"{url}\">x</a></p>\n"),
+                "{url}"
+            );
+        }
+    }
+}
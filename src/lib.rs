@@ -1,4 +1,11 @@
+pub mod audit;
+pub mod config;
+pub mod crypt;
 pub mod error;
+pub mod html;
+pub mod mail;
 pub mod middleware;
 pub mod model;
+pub mod oidc;
+pub mod validation;
 pub mod web;
@@ -0,0 +1,55 @@
+use std::path::PathBuf;
+
+use futures_util::{FutureExt, future::BoxFuture};
+use tokio::{fs::OpenOptions, io::AsyncWriteExt};
+
+use super::{Mail, Mailer};
+use crate::error::{MailError, Result};
+
+/// Appends mails to a file, or prints them to stdout. Meant for development
+/// and tests, where the file can be read back to pick up tokens.
+pub struct FileMailer {
+    path: Option<PathBuf>,
+}
+
+impl FileMailer {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: Some(path.into()),
+        }
+    }
+
+    pub fn stdout() -> Self {
+        Self { path: None }
+    }
+}
+
+impl Mailer for FileMailer {
+    fn send<'a>(&'a self, mail: &'a Mail) -> BoxFuture<'a, Result<()>> {
+        async move {
+            let message = format!(
+                "To: {}\nSubject: {}\n\n{}\n\n",
+                mail.to, mail.subject, mail.body
+            );
+
+            let Some(path) = &self.path else {
+                print!("{message}");
+                return Ok(());
+            };
+
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .await
+                .map_err(|_| MailError::SendFailed)?;
+
+            file.write_all(message.as_bytes())
+                .await
+                .map_err(|_| MailError::SendFailed)?;
+
+            Ok(())
+        }
+        .boxed()
+    }
+}
@@ -0,0 +1,45 @@
+mod file;
+mod smtp;
+
+pub use file::FileMailer;
+pub use smtp::SmtpMailer;
+
+use std::sync::LazyLock;
+
+use futures_util::future::BoxFuture;
+
+use crate::{config::config, error::Result};
+
+static MAILER: LazyLock<Box<dyn Mailer>> = LazyLock::new(|| {
+    let mail = &config().mail;
+
+    match mail.transport.as_str() {
+        "stdout" => Box::new(FileMailer::stdout()),
+        "file" => Box::new(FileMailer::new(
+            mail.file
+                .clone()
+                .expect("MAIL_FILE must be set for the file transport"),
+        )),
+        "smtp" => Box::new(SmtpMailer::new(
+            mail.smtp_url
+                .as_deref()
+                .expect("MAIL_SMTP_URL must be set for the smtp transport"),
+            &mail.from,
+        )),
+        transport => panic!("Unsupported MAIL_TRANSPORT {transport:?}"),
+    }
+});
+
+pub fn mailer() -> &'static dyn Mailer {
+    MAILER.as_ref()
+}
+
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+pub trait Mailer: Send + Sync {
+    fn send<'a>(&'a self, mail: &'a Mail) -> BoxFuture<'a, Result<()>>;
+}
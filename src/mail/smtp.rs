@@ -0,0 +1,51 @@
+use futures_util::{FutureExt, future::BoxFuture};
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor, message::Mailbox};
+use tracing::error;
+
+use super::{Mail, Mailer};
+use crate::error::{MailError, Result};
+
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn new(url: &str, from: &str) -> Self {
+        let transport = AsyncSmtpTransport::<Tokio1Executor>::from_url(url)
+            .unwrap_or_else(|e| panic!("Invalid MAIL_SMTP_URL: {e}"))
+            .build();
+
+        let from = from
+            .parse()
+            .unwrap_or_else(|e| panic!("Invalid MAIL_FROM {from:?}: {e}"));
+
+        Self { transport, from }
+    }
+}
+
+impl Mailer for SmtpMailer {
+    fn send<'a>(&'a self, mail: &'a Mail) -> BoxFuture<'a, Result<()>> {
+        async move {
+            let to = mail
+                .to
+                .parse::<Mailbox>()
+                .map_err(|_| MailError::InvalidAddress(mail.to.clone()))?;
+
+            let message = Message::builder()
+                .from(self.from.clone())
+                .to(to)
+                .subject(&mail.subject)
+                .body(mail.body.clone())
+                .map_err(|_| MailError::SendFailed)?;
+
+            self.transport.send(message).await.map_err(|e| {
+                error!("[{:^12}] ━ smtp {e}", "Mail");
+                MailError::SendFailed
+            })?;
+
+            Ok(())
+        }
+        .boxed()
+    }
+}
@@ -15,8 +15,14 @@ use tower_cookies::CookieManagerLayer;
 use tracing::info;
 
 use webserver::{
+    crypt::keys::keys,
     error::{Error, Result},
-    web::{login, register, room, ticket},
+    mail::mailer,
+    middleware::revocation,
+    web::{
+        Sensitive, account, admin, api_key, login, logout, mfa, oidc, password, register, room,
+        ticket, token,
+    },
 };
 
 #[tokio::main]
@@ -28,14 +34,30 @@ async fn main() -> result::Result<(), Box<dyn std::error::Error>> {
         .with_level(true)
         .init();
 
+    // Fail fast on a bad key or mail configuration instead of on the first
+    // login or inside the task sending the first mail.
+    keys();
+    mailer();
+
     let pool = SqlitePool::connect(&env::var("DATABASE_URL")?).await?;
+    revocation::load(pool.clone()).await?;
+    admin::promote_configured(&pool).await?;
+    tokio::spawn(ticket::purge(pool.clone()));
 
     let app = Router::new()
         .route("/", routing::get(handler_root))
-        .merge(room::router())
+        .merge(room::router(pool.clone()))
         .merge(ticket::router(pool.clone()))
         .merge(login::router(pool.clone()))
+        .merge(logout::router(pool.clone()))
         .merge(register::router(pool.clone()))
+        .merge(password::router(pool.clone()))
+        .merge(mfa::router(pool.clone()))
+        .merge(api_key::router(pool.clone()))
+        .merge(oidc::router(pool.clone()))
+        .merge(token::router(pool.clone()))
+        .merge(admin::router(pool.clone()))
+        .merge(account::router(pool.clone()))
         .layer(CookieManagerLayer::new())
         .layer(middleware::map_request(requset_input))
         .layer(middleware::map_response(response_output))
@@ -71,6 +93,7 @@ async fn response_output(response: Response) -> Result<impl IntoResponse> {
     let version = response.version();
     let status = response.status();
     let headers = response.headers().clone();
+    let sensitive = response.extensions().get::<Sensitive>().is_some();
 
     let Ok(bytes) = body::to_bytes(response.into_body(), usize::MAX).await else {
         return Response::builder()
@@ -79,7 +102,10 @@ async fn response_output(response: Response) -> Result<impl IntoResponse> {
             .map_err(|_| Error::Unknown);
     };
 
-    let body = String::from_utf8_lossy(&bytes);
+    let body = match sensitive {
+        true => "<redacted>".into(),
+        false => String::from_utf8_lossy(&bytes),
+    };
     info!(
         "[{:^12}] ┗━━━ status: {}, body: {:?}",
         "Output", status, body
@@ -0,0 +1,143 @@
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{ConnectInfo, FromRef, FromRequestParts, State},
+    http::{HeaderMap, header, request::Parts},
+};
+use chrono::{Duration, Utc};
+use sqlx::{Pool, Sqlite};
+use tracing::{info, warn};
+
+use crate::{
+    config::config,
+    crypt::token,
+    error::{AuthError, DatabaseError, Error, Result},
+    middleware::{jwt::Claims, revocation::revocations, role::Role},
+};
+
+/// Marks a bearer token as API key rather than JWT.
+pub const PREFIX: &str = "wsk_";
+pub const HEADER: &str = "x-api-key";
+
+pub fn generate() -> String {
+    format!("{PREFIX}{}", token::generate())
+}
+
+/// The key from `X-Api-Key`, or from `Authorization: Bearer` if it has the
+/// API key prefix.
+pub fn from_headers(headers: &HeaderMap) -> Option<&str> {
+    if let Some(key) = headers.get(HEADER) {
+        return key.to_str().ok();
+    }
+
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .filter(|token| token.starts_with(PREFIX))
+}
+
+/// Long-lived key of a user for scripts. The user's current role applies,
+/// and only the hash of the key is stored.
+pub struct ApiKey {
+    pub id: i64,
+    pub username: String,
+    pub role: Role,
+    pub scopes: String,
+    pub expires_at: Option<i64>,
+}
+
+impl<S> FromRequestParts<S> for ApiKey
+where
+    S: Send + Sync,
+    Pool<Sqlite>: FromRef<S>,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self> {
+        info!("[{:^12}] ┃ api key", "Middleware");
+
+        let key_hash = from_headers(&parts.headers)
+            .map(token::hash)
+            .ok_or(AuthError::InvalidApiKey)?;
+
+        let State(pool) = State::<Pool<Sqlite>>::from_request_parts(parts, state)
+            .await
+            .map_err(|_| Error::Unknown)?;
+
+        let now = Utc::now().timestamp();
+
+        let api_key = sqlx::query_as!(
+            ApiKey,
+            r#"
+            select
+                api_keys.id as "id!",
+                api_keys.username as "username!",
+                users.role as "role!: Role",
+                api_keys.scopes as "scopes!",
+                api_keys.expires_at
+            from api_keys
+            join users on users.username = api_keys.username
+            where api_keys.key_hash = ?
+            and api_keys.revoked_at is null
+            and (api_keys.expires_at is null or api_keys.expires_at > ?)
+            "#,
+            key_hash,
+            now,
+        )
+        .fetch_optional(&pool)
+        .await
+        .map_err(|_| DatabaseError::SelectFailed)?
+        .ok_or(AuthError::InvalidApiKey)?;
+
+        let ip = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip().to_string());
+
+        // Failing to record usage shouldn't fail the request.
+        if let Err(e) = sqlx::query!(
+            r#"
+            update api_keys
+            set last_used_at = ?, last_used_ip = ?
+            where id = ?
+            "#,
+            now,
+            ip,
+            api_key.id,
+        )
+        .execute(&pool)
+        .await
+        {
+            warn!(
+                "[{:^12}] ┃ record use of api key {}: {e:?}",
+                "Middleware", api_key.id
+            );
+        }
+
+        Ok(api_key)
+    }
+}
+
+/// Claims are built per request, so logging out everywhere doesn't end API
+/// keys. They are revoked one by one, or all at once with a password change.
+impl From<ApiKey> for Claims {
+    fn from(api_key: ApiKey) -> Self {
+        let now = Utc::now();
+        let exp = api_key
+            .expires_at
+            .unwrap_or((now + Duration::seconds(config().token.access_ttl)).timestamp());
+        let iat = revocations().issued_at(&api_key.username);
+
+        Self {
+            sub: api_key.username,
+            exp: exp as usize,
+            iat: iat as usize,
+            jti: format!("api-key:{}", api_key.id),
+            role: api_key.role,
+            sid: None,
+            scope: Some(api_key.scopes).filter(|scopes| !scopes.is_empty()),
+            act: None,
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use axum::http::request::Parts;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+use tower_cookies::{
+    Cookie, Cookies,
+    cookie::{SameSite, time},
+};
+use tracing::info;
+
+use crate::{
+    config::config,
+    error::{AuthError, Result},
+    middleware::session::{KEY, Session},
+};
+
+pub const COOKIE_NAME: &str = "csrf";
+pub const HEADER: &str = "x-csrf-token";
+
+/// Token bound to the session, so it needs no storage of its own and can't
+/// be planted by setting the cookie from a sibling domain.
+pub fn token(id_hash: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(KEY.signing()).expect("HMAC accepts keys of any length");
+    mac.update(id_hash.as_bytes());
+
+    format!("{:x}", mac.finalize().into_bytes())
+}
+
+/// Hands the token to the page in a cookie its scripts can read, to be sent
+/// back in the `X-CSRF-Token` header.
+pub fn set(cookies: &Cookies, id_hash: &str) {
+    let session = &config().session;
+
+    let cookie = Cookie::build((COOKIE_NAME, token(id_hash)))
+        .path("/")
+        .same_site(SameSite::Lax)
+        .secure(session.secure)
+        .max_age(time::Duration::seconds(session.ttl))
+        .build();
+
+    cookies.add(cookie);
+}
+
+pub fn remove(cookies: &Cookies) {
+    cookies.remove(Cookie::build(COOKIE_NAME).path("/").build());
+}
+
+/// Safe methods pass, everything else needs the token of the session.
+pub fn verify(parts: &Parts, session: &Session) -> Result<()> {
+    info!("[{:^12}] ┃ csrf", "Middleware");
+
+    if parts.method.is_safe() {
+        return Ok(());
+    }
+
+    let expected = token(&session.id_hash);
+
+    let valid = parts
+        .headers
+        .get(HEADER)
+        .is_some_and(|header| bool::from(header.as_bytes().ct_eq(expected.as_bytes())));
+
+    match valid {
+        true => Ok(()),
+        false => Err(AuthError::InvalidCsrfToken.into()),
+    }
+}
@@ -0,0 +1,43 @@
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    http::request::Parts,
+    middleware::{self, FromExtractorLayer},
+};
+use sqlx::{Pool, Sqlite};
+use tracing::info;
+
+use crate::{
+    error::{AuthError, Error, Result},
+    middleware::jwt::Claims,
+};
+
+/// Rejects impersonation tokens, for routes that change how the user signs in
+/// or hand out credentials, see [`forbid_impersonation`].
+pub struct NotImpersonating;
+
+impl<S> FromRequestParts<S> for NotImpersonating
+where
+    S: Send + Sync,
+    Pool<Sqlite>: FromRef<S>,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self> {
+        info!("[{:^12}] ┃ not impersonating", "Middleware");
+
+        let claims = Claims::from_request_parts(parts, state).await?;
+
+        match claims.act {
+            Some(_) => Err(AuthError::Impersonating.into()),
+            None => Ok(Self),
+        }
+    }
+}
+
+/// Per route, e.g.
+/// `routing::post(change).route_layer(forbid_impersonation(&pool))`.
+pub fn forbid_impersonation(
+    pool: &Pool<Sqlite>,
+) -> FromExtractorLayer<NotImpersonating, Pool<Sqlite>> {
+    middleware::from_extractor_with_state(pool.clone())
+}
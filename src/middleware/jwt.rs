@@ -1,23 +1,84 @@
-use axum::{extract::FromRequestParts, http::request::Parts};
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    http::request::Parts,
+};
 use axum_extra::{
     TypedHeader,
     headers::{Authorization, authorization::Bearer},
 };
-use jsonwebtoken::{DecodingKey, Validation};
 use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
 use tracing::info;
 
-use crate::error::{AuthError, Error, Result};
+use crate::{
+    crypt::keys::keys,
+    error::{AuthError, Error, Result},
+    middleware::{
+        api_key::{self, ApiKey},
+        csrf,
+        revocation::revocations,
+        role::Role,
+        scope,
+        session::Session,
+    },
+};
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Claims {
     pub sub: String,
     pub exp: usize,
+    pub iat: usize,
+    pub jti: String,
+    pub role: Role,
+    /// Refresh token family the token was issued with, ended on logout.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+    /// Space separated scopes the token is limited to, unlimited if absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// Admin acting as `sub`, set on impersonation tokens.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<Actor>,
+}
+
+/// `act` claim of RFC 8693, naming who actually holds the token.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Actor {
+    pub sub: String,
 }
 
+impl Claims {
+    pub fn has_scope(&self, required: &str) -> bool {
+        self.scope
+            .as_deref()
+            .is_none_or(|scopes| scope::grants(scopes, required))
+    }
+
+    /// Admin holding the token while impersonating `sub`.
+    pub fn actor(&self) -> Option<&str> {
+        self.act.as_ref().map(|act| act.sub.as_str())
+    }
+
+    /// Scope of a key or token created with this token. Requesting nothing
+    /// keeps the current limits, see [`Validator::scopes`] for rejecting
+    /// scopes the token doesn't have.
+    ///
+    /// [`Validator::scopes`]: crate::validation::Validator::scopes
+    pub fn narrow_scope(&self, requested: &[String]) -> Option<String> {
+        match requested.is_empty() {
+            true => self.scope.clone(),
+            false => Some(requested.join(" ")),
+        }
+    }
+}
+
+/// Accepts an API key, an `Authorization: Bearer` token or, for browser
+/// clients, the session cookie. Only the cookie is sent by the browser on its
+/// own, so only requests authenticated by it are checked for CSRF.
 impl<S> FromRequestParts<S> for Claims
 where
     S: Send + Sync,
+    Pool<Sqlite>: FromRef<S>,
 {
     type Rejection = Error;
 
@@ -28,21 +89,36 @@ where
             return Ok(claims.clone());
         };
 
-        let TypedHeader(Authorization(bearer)) =
-            TypedHeader::<Authorization<Bearer>>::from_request_parts(parts, state)
+        let bearer = TypedHeader::<Authorization<Bearer>>::from_request_parts(parts, state).await;
+
+        let claims = if api_key::from_headers(&parts.headers).is_some() {
+            ApiKey::from_request_parts(parts, state).await?.into()
+        } else if let Ok(TypedHeader(Authorization(bearer))) = bearer {
+            keys().decode::<Claims>(bearer.token())?
+        } else {
+            let session = Session::from_request_parts(parts, state)
                 .await
                 .map_err(|_| AuthError::InvalidToken)?;
 
-        let token_data = jsonwebtoken::decode::<Claims>(
-            bearer.token(),
-            &DecodingKey::from_secret(b"secret"),
-            &Validation::default(),
-        )
-        .map_err(|_| AuthError::InvalidToken)?;
+            csrf::verify(parts, &session)?;
+
+            session.into()
+        };
+
+        if revocations().is_revoked(&claims) {
+            return Err(AuthError::RevokedToken.into());
+        }
+
+        if let Some(actor) = claims.actor() {
+            info!(
+                "[{:^12}] ┃ {} impersonating {}",
+                "Middleware", actor, claims.sub
+            );
+        }
 
-        parts.extensions.insert(token_data.claims.clone());
+        parts.extensions.insert(claims.clone());
 
-        Ok(token_data.claims)
+        Ok(claims)
     }
 }
 
@@ -50,13 +126,17 @@ where
 pub struct AuthBody {
     access_token: String,
     token_type: String,
+    expires_in: i64,
+    refresh_token: String,
 }
 
 impl AuthBody {
-    pub fn new(access_token: String) -> Self {
+    pub fn new(access_token: String, expires_in: i64, refresh_token: String) -> Self {
         Self {
             access_token,
             token_type: "Bearer".into(),
+            expires_in,
+            refresh_token,
         }
     }
 }
@@ -1,2 +1,10 @@
-pub mod cookie;
+pub mod api_key;
+pub mod client;
+pub mod csrf;
+pub mod impersonation;
 pub mod jwt;
+pub mod revocation;
+pub mod role;
+pub mod scope;
+pub mod session;
+pub mod throttle;
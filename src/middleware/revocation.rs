@@ -0,0 +1,255 @@
+use std::sync::OnceLock;
+
+use chrono::Utc;
+use dashmap::DashMap;
+use sqlx::{Pool, Sqlite};
+use tokio::sync::broadcast;
+
+use crate::{
+    config::config,
+    error::{DatabaseError, Result},
+    middleware::jwt::Claims,
+};
+
+static REVOCATIONS: OnceLock<Revocations> = OnceLock::new();
+
+pub fn revocations() -> &'static Revocations {
+    REVOCATIONS
+        .get()
+        .expect("Revocations are not loaded, call revocation::load first")
+}
+
+pub async fn load(pool: Pool<Sqlite>) -> Result<()> {
+    let now = Utc::now().timestamp();
+
+    let tokens = sqlx::query!(
+        r#"
+        select jti, expires_at
+        from revoked_tokens
+        where expires_at > ?
+        "#,
+        now,
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?;
+
+    let users = sqlx::query!(
+        r#"
+        select username, revoked_before
+        from revoked_users
+        "#,
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?;
+
+    // Access tokens outlive the refresh tokens they were issued with by at
+    // most their lifetime.
+    let since = now - config().token.access_ttl;
+
+    let families = sqlx::query!(
+        r#"
+        select family as "family!", max(revoked_at) as "revoked_at!: i64"
+        from refresh_tokens
+        where revoked_at > ?
+        group by family
+        "#,
+        since,
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?;
+
+    let (sender, _) = broadcast::channel(128);
+
+    let revocations = Revocations {
+        pool,
+        tokens: tokens.into_iter().map(|t| (t.jti, t.expires_at)).collect(),
+        users: users
+            .into_iter()
+            .map(|u| (u.username, u.revoked_before))
+            .collect(),
+        families: families
+            .into_iter()
+            .map(|f| (f.family, f.revoked_at))
+            .collect(),
+        sender,
+    };
+
+    let _ = REVOCATIONS.set(revocations);
+
+    Ok(())
+}
+
+#[derive(Clone, Debug)]
+pub enum Revoked {
+    Token(String),
+    /// Access tokens issued along with a refresh token family.
+    Family(String),
+    User {
+        username: String,
+        before: i64,
+    },
+    /// The account was deleted, so everything tied to it should go as well.
+    Deleted(String),
+}
+
+impl Revoked {
+    pub fn applies_to(&self, claims: &Claims) -> bool {
+        match self {
+            Revoked::Token(jti) => claims.jti == *jti,
+            Revoked::Family(family) => claims.sid.as_ref() == Some(family),
+            Revoked::User { username, before } => {
+                is_holder(claims, username) && (claims.iat as i64) <= *before
+            }
+            Revoked::Deleted(username) => is_holder(claims, username),
+        }
+    }
+}
+
+/// Impersonation tokens go with the tokens of the admin holding them as well
+/// as with those of the impersonated user.
+fn is_holder(claims: &Claims, username: &str) -> bool {
+    claims.sub == username || claims.actor() == Some(username)
+}
+
+/// In-memory copy of the revocation tables, so checking a token on every
+/// request doesn't cost a query. Writes go to both and are broadcast so
+/// long-lived connections such as chat sockets can be closed.
+pub struct Revocations {
+    pool: Pool<Sqlite>,
+    tokens: DashMap<String, i64>,
+    users: DashMap<String, i64>,
+    /// Revoked refresh token families and when, the revocations themselves
+    /// are kept in `refresh_tokens`.
+    families: DashMap<String, i64>,
+    sender: broadcast::Sender<Revoked>,
+}
+
+impl Revocations {
+    pub fn is_revoked(&self, claims: &Claims) -> bool {
+        let revoked_user = |username: &str| {
+            self.users
+                .get(username)
+                .is_some_and(|before| (claims.iat as i64) <= *before)
+        };
+
+        self.tokens.contains_key(&claims.jti)
+            || claims
+                .sid
+                .as_ref()
+                .is_some_and(|family| self.families.contains_key(family))
+            || revoked_user(&claims.sub)
+            || claims.actor().is_some_and(revoked_user)
+    }
+
+    /// `iat` for a token of `username` issued now. `iat` only has seconds,
+    /// so a revocation takes the rest of its second with it and later tokens
+    /// are dated past it.
+    pub fn issued_at(&self, username: &str) -> i64 {
+        let now = Utc::now().timestamp();
+
+        self.users
+            .get(username)
+            .map_or(now, |before| now.max(*before + 1))
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Revoked> {
+        self.sender.subscribe()
+    }
+
+    pub async fn revoke_token(&self, claims: &Claims) -> Result<()> {
+        let now = Utc::now().timestamp();
+        let expires_at = claims.exp as i64;
+
+        sqlx::query!(
+            r#"
+            insert into revoked_tokens (jti, username, expires_at)
+            values (?, ?, ?)
+            on conflict (jti) do nothing
+            "#,
+            claims.jti,
+            claims.sub,
+            expires_at,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| DatabaseError::InsertFailed)?;
+
+        // Expired tokens are rejected anyway, no need to remember them.
+        sqlx::query!(
+            r#"
+            delete from revoked_tokens
+            where expires_at <= ?
+            "#,
+            now,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| DatabaseError::DeleteFailed)?;
+
+        self.tokens.retain(|_, expires_at| *expires_at > now);
+        self.tokens.insert(claims.jti.clone(), expires_at);
+
+        let _ = self.sender.send(Revoked::Token(claims.jti.clone()));
+
+        Ok(())
+    }
+
+    /// Rejects the access tokens issued with a refresh token family, once the
+    /// family itself is revoked.
+    pub fn revoke_family(&self, family: &str) {
+        let now = Utc::now().timestamp();
+        let since = now - config().token.access_ttl;
+
+        self.families.retain(|_, revoked_at| *revoked_at > since);
+        self.families.insert(family.to_string(), now);
+
+        let _ = self.sender.send(Revoked::Family(family.to_string()));
+    }
+
+    /// Revokes every token of `username` issued up to now.
+    pub async fn revoke_user(&self, username: &str) -> Result<()> {
+        let now = self.revoke_before(username).await?;
+
+        let _ = self.sender.send(Revoked::User {
+            username: username.to_string(),
+            before: now,
+        });
+
+        Ok(())
+    }
+
+    /// Rejects the tokens of a deleted user, also after a restart and for a
+    /// new account registered under the same name.
+    pub async fn remove_user(&self, username: &str) -> Result<()> {
+        self.revoke_before(username).await?;
+
+        let _ = self.sender.send(Revoked::Deleted(username.to_string()));
+
+        Ok(())
+    }
+
+    async fn revoke_before(&self, username: &str) -> Result<i64> {
+        let now = Utc::now().timestamp();
+
+        sqlx::query!(
+            r#"
+            insert into revoked_users (username, revoked_before)
+            values (?, ?)
+            on conflict (username) do update
+            set revoked_before = excluded.revoked_before
+            "#,
+            username,
+            now,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| DatabaseError::InsertFailed)?;
+
+        self.users.insert(username.to_string(), now);
+
+        Ok(now)
+    }
+}
@@ -0,0 +1,80 @@
+use std::marker::PhantomData;
+
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    http::request::Parts,
+    middleware::{self, FromExtractorLayer},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+use tracing::info;
+
+use crate::{
+    error::{AuthError, Error, Result},
+    middleware::jwt::Claims,
+};
+
+/// Ordered by privilege, so `role >= Role::Member` reads as "at least member".
+#[derive(
+    Serialize, Deserialize, sqlx::Type, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum Role {
+    Viewer,
+    Member,
+    Admin,
+}
+
+pub trait RoleMarker {
+    const ROLE: Role;
+}
+
+pub struct Admin;
+pub struct Member;
+pub struct Viewer;
+
+impl RoleMarker for Admin {
+    const ROLE: Role = Role::Admin;
+}
+
+impl RoleMarker for Member {
+    const ROLE: Role = Role::Member;
+}
+
+impl RoleMarker for Viewer {
+    const ROLE: Role = Role::Viewer;
+}
+
+/// Rejects requests whose token doesn't carry at least role `R`, either as a
+/// handler argument or per route through [`require_role`].
+pub struct RequireRole<R>(PhantomData<fn() -> R>);
+
+impl<S, R> FromRequestParts<S> for RequireRole<R>
+where
+    S: Send + Sync,
+    Pool<Sqlite>: FromRef<S>,
+    R: RoleMarker,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self> {
+        info!("[{:^12}] ┃ role {:?}", "Middleware", R::ROLE);
+
+        let claims = Claims::from_request_parts(parts, state).await?;
+
+        if claims.role < R::ROLE {
+            return Err(AuthError::Forbidden.into());
+        }
+
+        Ok(Self(PhantomData))
+    }
+}
+
+/// Per route permission requirement, e.g.
+/// `routing::delete(delete).route_layer(require_role::<Admin>(&pool))`.
+pub fn require_role<R: RoleMarker>(
+    pool: &Pool<Sqlite>,
+) -> FromExtractorLayer<RequireRole<R>, Pool<Sqlite>> {
+    middleware::from_extractor_with_state(pool.clone())
+}
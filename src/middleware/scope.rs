@@ -0,0 +1,133 @@
+use std::marker::PhantomData;
+
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    http::request::Parts,
+    middleware::{self, FromExtractorLayer},
+};
+use sqlx::{Pool, Sqlite};
+use tracing::info;
+
+use crate::{
+    error::{AuthError, Error, Result},
+    middleware::jwt::Claims,
+};
+
+/// Every scope a token can be limited to, as `resource:level`.
+pub const SCOPES: &[&str] = &[
+    "account:read",
+    "account:write",
+    "audit:read",
+    "chat:read",
+    "chat:write",
+    "chat:admin",
+    "tickets:read",
+    "tickets:write",
+    "users:admin",
+];
+
+/// Levels in increasing order, a scope grants every lower level of the same
+/// resource, so `tickets:write` includes `tickets:read`.
+const LEVELS: &[&str] = &["read", "write", "admin"];
+
+pub fn is_known(scope: &str) -> bool {
+    SCOPES.contains(&scope)
+}
+
+/// Whether the space separated `scopes` of a token grant `required`.
+pub fn grants(scopes: &str, required: &str) -> bool {
+    let Some((resource, level)) = required.split_once(':') else {
+        return false;
+    };
+    let rank = |level| LEVELS.iter().position(|l| *l == level);
+
+    scopes.split_whitespace().any(|scope| {
+        scope.split_once(':').is_some_and(|(r, l)| {
+            r == resource && rank(l).is_some_and(|held| Some(held) >= rank(level))
+        })
+    })
+}
+
+pub trait ScopeMarker {
+    const SCOPE: &'static str;
+}
+
+pub struct AccountRead;
+pub struct AccountWrite;
+pub struct AuditRead;
+pub struct ChatRead;
+pub struct ChatWrite;
+pub struct ChatAdmin;
+pub struct TicketsRead;
+pub struct TicketsWrite;
+pub struct UsersAdmin;
+
+impl ScopeMarker for AccountRead {
+    const SCOPE: &'static str = "account:read";
+}
+
+impl ScopeMarker for AccountWrite {
+    const SCOPE: &'static str = "account:write";
+}
+
+impl ScopeMarker for AuditRead {
+    const SCOPE: &'static str = "audit:read";
+}
+
+impl ScopeMarker for ChatRead {
+    const SCOPE: &'static str = "chat:read";
+}
+
+impl ScopeMarker for ChatWrite {
+    const SCOPE: &'static str = "chat:write";
+}
+
+impl ScopeMarker for ChatAdmin {
+    const SCOPE: &'static str = "chat:admin";
+}
+
+impl ScopeMarker for TicketsRead {
+    const SCOPE: &'static str = "tickets:read";
+}
+
+impl ScopeMarker for TicketsWrite {
+    const SCOPE: &'static str = "tickets:write";
+}
+
+impl ScopeMarker for UsersAdmin {
+    const SCOPE: &'static str = "users:admin";
+}
+
+/// Rejects requests whose token is limited to scopes that don't include
+/// `S`. Tokens without a `scope` claim, such as those from a password login,
+/// pass every check, roles still apply to them.
+pub struct RequireScope<S>(PhantomData<fn() -> S>);
+
+impl<St, S> FromRequestParts<St> for RequireScope<S>
+where
+    St: Send + Sync,
+    Pool<Sqlite>: FromRef<St>,
+    S: ScopeMarker,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &St) -> Result<Self> {
+        info!("[{:^12}] ┃ scope {}", "Middleware", S::SCOPE);
+
+        let claims = Claims::from_request_parts(parts, state).await?;
+
+        if !claims.has_scope(S::SCOPE) {
+            return Err(AuthError::MissingScope(S::SCOPE).into());
+        }
+
+        Ok(Self(PhantomData))
+    }
+}
+
+/// Per route scope requirement, e.g.
+/// `routing::get(list).route_layer(require_scope::<TicketsRead>(&pool))`.
+pub fn require_scope<S: ScopeMarker>(
+    pool: &Pool<Sqlite>,
+) -> FromExtractorLayer<RequireScope<S>, Pool<Sqlite>> {
+    middleware::from_extractor_with_state(pool.clone())
+}
@@ -0,0 +1,187 @@
+use std::sync::LazyLock;
+
+use axum::{
+    extract::{FromRef, FromRequestParts, State},
+    http::request::Parts,
+};
+use chrono::Utc;
+use sha2::{Digest, Sha512};
+use sqlx::{Pool, Sqlite};
+use tower_cookies::{
+    Cookie, Cookies, Key,
+    cookie::{SameSite, time},
+};
+use tracing::{info, warn};
+
+use crate::{
+    config::config,
+    crypt::token,
+    error::{AuthError, DatabaseError, Error, Result},
+    middleware::{csrf, jwt::Claims, revocation::revocations, role::Role},
+};
+
+pub const COOKIE_NAME: &str = "session";
+
+pub(super) static KEY: LazyLock<Key> = LazyLock::new(|| match &config().session.secret {
+    Some(secret) => Key::from(&Sha512::digest(secret.as_bytes())),
+    None => {
+        warn!(
+            "[{:^12}] ━ SESSION_SECRET not set, sessions won't survive a restart",
+            "Session"
+        );
+
+        Key::generate()
+    }
+});
+
+/// Browser session, identified by an encrypted HttpOnly cookie that holds a
+/// random id. Only the hash of the id is stored.
+pub struct Session {
+    pub id_hash: String,
+    pub username: String,
+    pub role: Role,
+    pub created_at: i64,
+    pub expires_at: i64,
+}
+
+impl<S> FromRequestParts<S> for Session
+where
+    S: Send + Sync,
+    Pool<Sqlite>: FromRef<S>,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self> {
+        info!("[{:^12}] ┃ session", "Middleware");
+
+        let cookies = Cookies::from_request_parts(parts, state)
+            .await
+            .map_err(|_| AuthError::InvalidCookie)?;
+
+        let State(pool) = State::<Pool<Sqlite>>::from_request_parts(parts, state)
+            .await
+            .map_err(|_| Error::Unknown)?;
+
+        let id = cookies
+            .private(&KEY)
+            .get(COOKIE_NAME)
+            .map(|c| c.value().to_string())
+            .ok_or(AuthError::InvalidCookie)?;
+
+        let id_hash = token::hash(&id);
+        let now = Utc::now().timestamp();
+
+        sqlx::query_as!(
+            Session,
+            r#"
+            select
+                sessions.id_hash as "id_hash!",
+                sessions.username as "username!",
+                users.role as "role!: Role",
+                sessions.created_at as "created_at!",
+                sessions.expires_at as "expires_at!"
+            from sessions
+            join users on users.username = sessions.username
+            where sessions.id_hash = ?
+            and sessions.expires_at > ?
+            "#,
+            id_hash,
+            now,
+        )
+        .fetch_optional(&pool)
+        .await
+        .map_err(|_| DatabaseError::SelectFailed)?
+        .ok_or(AuthError::InvalidCookie.into())
+    }
+}
+
+impl From<Session> for Claims {
+    fn from(session: Session) -> Self {
+        Self {
+            sub: session.username,
+            exp: session.expires_at as usize,
+            iat: session.created_at as usize,
+            jti: session.id_hash,
+            role: session.role,
+            sid: None,
+            scope: None,
+            act: None,
+        }
+    }
+}
+
+pub async fn create(pool: &Pool<Sqlite>, cookies: &Cookies, username: &str) -> Result<()> {
+    let session = &config().session;
+
+    let id = token::generate();
+    let id_hash = token::hash(&id);
+    let created_at = revocations().issued_at(username);
+    let expires_at = created_at + session.ttl;
+
+    sqlx::query!(
+        r#"
+        insert into sessions (id_hash, username, created_at, expires_at)
+        values (?, ?, ?, ?)
+        "#,
+        id_hash,
+        username,
+        created_at,
+        expires_at,
+    )
+    .execute(pool)
+    .await
+    .map_err(|_| DatabaseError::InsertFailed)?;
+
+    let cookie = Cookie::build((COOKIE_NAME, id))
+        .path("/")
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .secure(session.secure)
+        .max_age(time::Duration::seconds(session.ttl))
+        .build();
+
+    cookies.private(&KEY).add(cookie);
+    csrf::set(cookies, &id_hash);
+
+    Ok(())
+}
+
+/// Ends the session of the current request, if there is one.
+pub async fn remove(pool: &Pool<Sqlite>, cookies: &Cookies) -> Result<()> {
+    let private = cookies.private(&KEY);
+
+    if let Some(cookie) = private.get(COOKIE_NAME) {
+        let id_hash = token::hash(cookie.value());
+
+        sqlx::query!(
+            r#"
+            delete from sessions
+            where id_hash = ?
+            "#,
+            id_hash,
+        )
+        .execute(pool)
+        .await
+        .map_err(|_| DatabaseError::DeleteFailed)?;
+    }
+
+    private.remove(Cookie::build(COOKIE_NAME).path("/").build());
+    csrf::remove(cookies);
+
+    Ok(())
+}
+
+pub async fn remove_user(pool: &Pool<Sqlite>, username: &str) -> Result<()> {
+    sqlx::query!(
+        r#"
+        delete from sessions
+        where username = ?
+        "#,
+        username,
+    )
+    .execute(pool)
+    .await
+    .map_err(|_| DatabaseError::DeleteFailed)?;
+
+    Ok(())
+}
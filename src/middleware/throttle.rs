@@ -0,0 +1,132 @@
+use std::{net::IpAddr, sync::LazyLock};
+
+use chrono::Utc;
+use dashmap::DashMap;
+
+use crate::{
+    config::config,
+    error::{AuthError, Result},
+};
+
+/// Entries are pruned once a map grows past this size.
+const MAX_ENTRIES: usize = 10_000;
+
+static THROTTLE: LazyLock<LoginThrottle> = LazyLock::new(LoginThrottle::default);
+
+pub fn throttle() -> &'static LoginThrottle {
+    &THROTTLE
+}
+
+/// Tracks failed logins per account and per client address. Every failure
+/// doubles the wait before the next attempt, and an account is locked after
+/// `LOGIN_MAX_FAILURES` failures. State is kept in memory only.
+#[derive(Default)]
+pub struct LoginThrottle {
+    accounts: DashMap<String, Attempts>,
+    addresses: DashMap<IpAddr, Attempts>,
+}
+
+#[derive(Default)]
+struct Attempts {
+    failures: u32,
+    last_failure: i64,
+    next_attempt: i64,
+    locked_until: i64,
+}
+
+impl LoginThrottle {
+    pub fn check(&self, ip: IpAddr, username: &str) -> Result<()> {
+        let now = Utc::now().timestamp();
+
+        if let Some(account) = self.accounts.get(username) {
+            if account.locked_until > now {
+                return Err(AuthError::AccountLocked {
+                    retry_after: account.locked_until - now,
+                }
+                .into());
+            }
+
+            if account.next_attempt > now {
+                return Err(AuthError::TooManyAttempts {
+                    retry_after: account.next_attempt - now,
+                }
+                .into());
+            }
+        }
+
+        if let Some(address) = self.addresses.get(&ip)
+            && address.next_attempt > now
+        {
+            return Err(AuthError::TooManyAttempts {
+                retry_after: address.next_attempt - now,
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    pub fn failed(&self, ip: IpAddr, username: &str) {
+        let login = &config().login;
+        let now = Utc::now().timestamp();
+
+        self.prune(now);
+
+        let mut account = self.accounts.entry(username.to_string()).or_default();
+        account.record(now);
+
+        if account.failures >= login.max_failures {
+            account.failures = 0;
+            account.locked_until = now + login.lockout;
+        }
+
+        self.addresses.entry(ip).or_default().record(now);
+    }
+
+    /// Only the account is reset, otherwise a single valid account would let
+    /// one address guess the passwords of all others.
+    pub fn succeeded(&self, username: &str) {
+        self.accounts.remove(username);
+    }
+
+    pub fn unlock(&self, username: &str) -> bool {
+        self.accounts.remove(username).is_some()
+    }
+
+    fn prune(&self, now: i64) {
+        let window = config().login.lockout;
+
+        if self.accounts.len() > MAX_ENTRIES {
+            self.accounts.retain(|_, a| a.is_recent(now, window));
+        }
+
+        if self.addresses.len() > MAX_ENTRIES {
+            self.addresses.retain(|_, a| a.is_recent(now, window));
+        }
+    }
+}
+
+impl Attempts {
+    fn record(&mut self, now: i64) {
+        let login = &config().login;
+
+        // Failures from long ago don't count towards the backoff.
+        if !self.is_recent(now, login.lockout) {
+            self.failures = 0;
+        }
+
+        self.failures += 1;
+        self.last_failure = now;
+
+        let backoff = login
+            .backoff_base
+            .saturating_mul(1 << (self.failures - 1).min(30))
+            .min(login.backoff_max);
+
+        self.next_attempt = now + backoff;
+    }
+
+    fn is_recent(&self, now: i64, window: i64) -> bool {
+        self.last_failure + window > now || self.locked_until > now
+    }
+}
@@ -0,0 +1,80 @@
+use chrono::Utc;
+use jsonwebtoken::{DecodingKey, jwk::JwkSet};
+use tokio::sync::RwLock;
+use tracing::info;
+
+use crate::error::{OidcError, Result};
+
+/// Minimum seconds between two fetches, so tokens with unknown key ids
+/// can't make us hammer the provider.
+const MIN_REFRESH: i64 = 30;
+
+/// Signing keys of the provider. They are fetched again once they're older
+/// than `OIDC_JWKS_TTL` or a token names a key id we haven't seen, which
+/// happens after the provider rotated its keys.
+#[derive(Default)]
+pub struct Jwks {
+    cached: RwLock<Option<Cached>>,
+}
+
+struct Cached {
+    keys: JwkSet,
+    fetched_at: i64,
+}
+
+impl Jwks {
+    pub async fn key(
+        &self,
+        http: &reqwest::Client,
+        uri: &str,
+        kid: Option<&str>,
+        ttl: i64,
+    ) -> Result<DecodingKey> {
+        let now = Utc::now().timestamp();
+
+        if let Some(cached) = self.cached.read().await.as_ref() {
+            let fresh = cached.fetched_at + ttl > now;
+
+            match find(&cached.keys, kid) {
+                Some(key) if fresh => return key,
+                None if cached.fetched_at + MIN_REFRESH > now => {
+                    return Err(OidcError::InvalidIdToken.into());
+                }
+                _ => {}
+            }
+        }
+
+        info!("[{:^12}] ━ fetching signing keys", "Oidc");
+
+        let keys = http
+            .get(uri)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|_| OidcError::ProviderUnavailable)?
+            .json::<JwkSet>()
+            .await
+            .map_err(|_| OidcError::ProviderUnavailable)?;
+
+        let key = find(&keys, kid);
+
+        *self.cached.write().await = Some(Cached {
+            keys,
+            fetched_at: now,
+        });
+
+        key.unwrap_or(Err(OidcError::InvalidIdToken.into()))
+    }
+}
+
+/// Tokens without a key id are only accepted while the provider has a
+/// single key.
+fn find(keys: &JwkSet, kid: Option<&str>) -> Option<Result<DecodingKey>> {
+    let jwk = match kid {
+        Some(kid) => keys.find(kid),
+        None if keys.keys.len() == 1 => keys.keys.first(),
+        None => None,
+    }?;
+
+    Some(DecodingKey::from_jwk(jwk).map_err(|_| OidcError::InvalidIdToken.into()))
+}
@@ -0,0 +1,280 @@
+mod jwks;
+
+use std::sync::LazyLock;
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::Utc;
+use dashmap::DashMap;
+use jsonwebtoken::Validation;
+use reqwest::Url;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tokio::sync::OnceCell;
+use tracing::warn;
+
+use crate::{
+    config::{OidcConfig, config},
+    crypt::token,
+    error::{OidcError, Result},
+};
+
+use jwks::Jwks;
+
+/// Logins that were started but not finished are pruned past this size.
+const MAX_PENDING: usize = 10_000;
+
+static PROVIDER: LazyLock<Option<Provider>> =
+    LazyLock::new(|| config().oidc.as_ref().map(Provider::new));
+
+pub fn provider() -> Result<&'static Provider> {
+    PROVIDER.as_ref().ok_or(OidcError::NotConfigured.into())
+}
+
+/// Client of an OpenID Connect provider using the authorization code flow
+/// with PKCE. The discovery document is fetched on first use, so the server
+/// starts even while the provider is unreachable.
+pub struct Provider {
+    config: &'static OidcConfig,
+    http: reqwest::Client,
+    metadata: OnceCell<Metadata>,
+    jwks: Jwks,
+    pending: DashMap<String, Pending>,
+}
+
+#[derive(Deserialize)]
+struct Metadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+    #[serde(default)]
+    id_token_signing_alg_values_supported: Vec<String>,
+}
+
+/// Kept between redirecting to the provider and its callback, keyed by the
+/// `state` parameter.
+struct Pending {
+    verifier: String,
+    nonce: String,
+    expires_at: i64,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+#[derive(Deserialize)]
+struct IdClaims {
+    sub: String,
+    nonce: Option<String>,
+    email: Option<String>,
+    email_verified: Option<bool>,
+    preferred_username: Option<String>,
+}
+
+pub struct Authorization {
+    pub url: String,
+    pub state: String,
+    pub expires_in: i64,
+}
+
+/// The verified identity of a user at the provider.
+pub struct Identity {
+    pub issuer: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub preferred_username: Option<String>,
+}
+
+impl Provider {
+    fn new(config: &'static OidcConfig) -> Self {
+        Self {
+            config,
+            http: reqwest::Client::new(),
+            metadata: OnceCell::new(),
+            jwks: Jwks::default(),
+            pending: DashMap::new(),
+        }
+    }
+
+    /// Starts a login and returns the URL of the provider to send the user to.
+    pub async fn authorize(&self) -> Result<Authorization> {
+        let metadata = self.metadata().await?;
+        let now = Utc::now().timestamp();
+
+        self.prune(now);
+
+        let state = token::generate();
+        let nonce = token::generate();
+        let verifier = token::generate();
+        let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+
+        let url = Url::parse_with_params(
+            &metadata.authorization_endpoint,
+            &[
+                ("response_type", "code"),
+                ("client_id", &self.config.client_id),
+                ("redirect_uri", &self.config.redirect_url),
+                ("scope", &self.config.scopes.join(" ")),
+                ("state", &state),
+                ("nonce", &nonce),
+                ("code_challenge", &challenge),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .map_err(|_| OidcError::ProviderUnavailable)?;
+
+        self.pending.insert(
+            state.clone(),
+            Pending {
+                verifier,
+                nonce,
+                expires_at: now + self.config.login_ttl,
+            },
+        );
+
+        Ok(Authorization {
+            url: url.into(),
+            state,
+            expires_in: self.config.login_ttl,
+        })
+    }
+
+    /// Exchanges the code from the callback and validates the ID token. The
+    /// state is spent either way, so a callback can't be replayed.
+    pub async fn finish(&self, state: &str, code: &str) -> Result<Identity> {
+        let (_, pending) = self
+            .pending
+            .remove(state)
+            .filter(|(_, p)| p.expires_at > Utc::now().timestamp())
+            .ok_or(OidcError::InvalidState)?;
+
+        let metadata = self.metadata().await?;
+
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", &self.config.redirect_url),
+            ("client_id", &self.config.client_id),
+            ("code_verifier", &pending.verifier),
+        ];
+
+        if let Some(secret) = &self.config.client_secret {
+            form.push(("client_secret", secret));
+        }
+
+        let response = self
+            .http
+            .post(&metadata.token_endpoint)
+            .form(&form)
+            .send()
+            .await
+            .map_err(|_| OidcError::ProviderUnavailable)?;
+
+        if !response.status().is_success() {
+            warn!(
+                "[{:^12}] ┃ code exchange failed with {}",
+                "Oidc",
+                response.status()
+            );
+
+            return Err(OidcError::ExchangeFailed.into());
+        }
+
+        let tokens = response
+            .json::<TokenResponse>()
+            .await
+            .map_err(|_| OidcError::ExchangeFailed)?;
+
+        let claims = self.validate(metadata, &tokens.id_token).await?;
+
+        if claims.nonce.as_deref() != Some(pending.nonce.as_str()) {
+            return Err(OidcError::InvalidIdToken.into());
+        }
+
+        Ok(Identity {
+            issuer: metadata.issuer.clone(),
+            subject: claims.sub,
+            email: claims.email,
+            email_verified: claims.email_verified.unwrap_or(false),
+            preferred_username: claims.preferred_username,
+        })
+    }
+
+    async fn validate(&self, metadata: &Metadata, id_token: &str) -> Result<IdClaims> {
+        let header =
+            jsonwebtoken::decode_header(id_token).map_err(|_| OidcError::InvalidIdToken)?;
+
+        // Keys of the provider are public, so shared-secret algorithms would
+        // let anyone sign tokens.
+        let supported = &metadata.id_token_signing_alg_values_supported;
+        let algorithm = format!("{:?}", header.alg);
+
+        if algorithm.starts_with("HS") || !(supported.is_empty() || supported.contains(&algorithm))
+        {
+            return Err(OidcError::InvalidIdToken.into());
+        }
+
+        let key = self
+            .jwks
+            .key(
+                &self.http,
+                &metadata.jwks_uri,
+                header.kid.as_deref(),
+                self.config.jwks_ttl,
+            )
+            .await?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&metadata.issuer]);
+        validation.set_audience(&[&self.config.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        let token_data = jsonwebtoken::decode::<IdClaims>(id_token, &key, &validation)
+            .map_err(|_| OidcError::InvalidIdToken)?;
+
+        Ok(token_data.claims)
+    }
+
+    async fn metadata(&self) -> Result<&Metadata> {
+        self.metadata
+            .get_or_try_init(|| async {
+                let url = format!(
+                    "{}/.well-known/openid-configuration",
+                    self.config.issuer.trim_end_matches('/')
+                );
+
+                let metadata = self
+                    .http
+                    .get(url)
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .map_err(|_| OidcError::ProviderUnavailable)?
+                    .json::<Metadata>()
+                    .await
+                    .map_err(|_| OidcError::ProviderUnavailable)?;
+
+                if metadata.issuer.trim_end_matches('/') != self.config.issuer.trim_end_matches('/')
+                {
+                    warn!(
+                        "[{:^12}] ┃ discovery issuer {:?} doesn't match OIDC_ISSUER",
+                        "Oidc", metadata.issuer
+                    );
+
+                    return Err(OidcError::ProviderUnavailable.into());
+                }
+
+                Ok(metadata)
+            })
+            .await
+    }
+
+    fn prune(&self, now: i64) {
+        if self.pending.len() > MAX_PENDING {
+            self.pending.retain(|_, p| p.expires_at > now);
+        }
+    }
+}
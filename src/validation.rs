@@ -0,0 +1,400 @@
+use std::{collections::HashSet, fs, sync::LazyLock};
+
+use reqwest::Url;
+use serde::de::{DeserializeOwned, IntoDeserializer, value};
+
+use crate::{
+    config::config,
+    error::{Result, ValidationError},
+    middleware::{jwt::Claims, scope},
+};
+
+const LABEL_MAX: usize = 64;
+const TITLE_MAX: usize = 200;
+const DESCRIPTION_MAX: usize = 10_000;
+const URL_MAX: usize = 2048;
+
+const COMMON_PASSWORDS: &[&str] = &[
+    "123456",
+    "12345678",
+    "123456789",
+    "1234567890",
+    "password",
+    "password1",
+    "password123",
+    "qwerty",
+    "qwerty123",
+    "qwertyuiop",
+    "abc123",
+    "111111",
+    "000000",
+    "iloveyou",
+    "admin123",
+    "welcome",
+    "welcome1",
+    "letmein",
+    "monkey",
+    "dragon",
+    "football",
+    "baseball",
+    "sunshine",
+    "princess",
+    "trustno1",
+    "starwars",
+    "whatever",
+    "passw0rd",
+    "p@ssw0rd",
+    "changeme",
+    "1q2w3e4r",
+    "zaq12wsx",
+    "superman",
+    "master",
+    "shadow",
+    "michael",
+    "hello123",
+];
+
+static BLOCKLIST: LazyLock<HashSet<String>> = LazyLock::new(|| {
+    let mut blocklist = COMMON_PASSWORDS
+        .iter()
+        .map(|p| p.to_string())
+        .collect::<HashSet<_>>();
+
+    if let Some(path) = &config().validation.password_blocklist {
+        let content = fs::read_to_string(path)
+            .unwrap_or_else(|e| panic!("Failed to read PASSWORD_BLOCKLIST_FILE {path:?}: {e}"));
+
+        blocklist.extend(
+            content
+                .lines()
+                .map(|line| line.trim().to_lowercase())
+                .filter(|line| !line.is_empty()),
+        );
+    }
+
+    blocklist
+});
+
+/// Collects problems with every field of a payload before failing, so the
+/// client gets all of them in a single 422 response.
+#[derive(Default)]
+pub struct Validator {
+    errors: ValidationError,
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rules shared by usernames and chat user and room names.
+    pub fn name(mut self, field: &'static str, value: &str) -> Self {
+        let validation = &config().validation;
+        let length = value.chars().count();
+
+        if length < validation.name_min || length > validation.name_max {
+            self.errors.add(
+                field,
+                format!(
+                    "must be between {} and {} characters",
+                    validation.name_min, validation.name_max
+                ),
+            );
+        }
+
+        if !value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        {
+            self.errors
+                .add(field, "may only contain letters, digits, '_', '-' and '.'");
+        }
+
+        if !value.starts_with(|c: char| c.is_ascii_alphanumeric()) {
+            self.errors.add(field, "must start with a letter or digit");
+        }
+
+        if validation
+            .reserved_names
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(value))
+        {
+            self.errors.add(field, "is reserved");
+        }
+
+        self
+    }
+
+    pub fn password(mut self, field: &'static str, value: &str, username: &str) -> Self {
+        let validation = &config().validation;
+        let length = value.chars().count();
+
+        if length < validation.password_min || length > validation.password_max {
+            self.errors.add(
+                field,
+                format!(
+                    "must be between {} and {} characters",
+                    validation.password_min, validation.password_max
+                ),
+            );
+        }
+
+        if BLOCKLIST.contains(&value.to_lowercase()) {
+            self.errors.add(field, "is too common");
+        } else if password_strength(value, username) < validation.password_min_strength {
+            self.errors.add(
+                field,
+                "is too weak, use a longer password or mix letters, digits and symbols",
+            );
+        }
+
+        self
+    }
+
+    pub fn email(mut self, field: &'static str, value: &str) -> Self {
+        let valid = value.split_once('@').is_some_and(|(local, domain)| {
+            !local.is_empty()
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+        });
+
+        if !valid || value.chars().any(char::is_whitespace) {
+            self.errors.add(field, "is not a valid email address");
+        }
+
+        self
+    }
+
+    /// Free-form display names such as API key names.
+    pub fn label(mut self, field: &'static str, value: &str) -> Self {
+        let length = value.trim().chars().count();
+
+        if length == 0 || length > LABEL_MAX {
+            self.errors.add(
+                field,
+                format!("must be between 1 and {LABEL_MAX} characters"),
+            );
+        }
+
+        if value.chars().any(char::is_control) {
+            self.errors.add(field, "may not contain control characters");
+        }
+
+        self
+    }
+
+    /// Names of ticket labels. Ticket filters list them comma separated, so
+    /// they can't contain commas themselves.
+    pub fn label_name(self, field: &'static str, value: &str) -> Self {
+        let mut validator = self.label(field, value);
+
+        if value.contains(',') {
+            validator.errors.add(field, "may not contain commas");
+        }
+
+        validator
+    }
+
+    /// Single line summaries such as ticket titles.
+    pub fn title(mut self, field: &'static str, value: &str) -> Self {
+        let length = value.trim().chars().count();
+
+        if length == 0 || length > TITLE_MAX {
+            self.errors.add(
+                field,
+                format!("must be between 1 and {TITLE_MAX} characters"),
+            );
+        }
+
+        if value.chars().any(char::is_control) {
+            self.errors.add(field, "may not contain control characters");
+        }
+
+        self
+    }
+
+    /// Multi-line text, which may be empty.
+    pub fn description(mut self, field: &'static str, value: &str) -> Self {
+        if value.chars().count() > DESCRIPTION_MAX {
+            self.errors.add(
+                field,
+                format!("must be at most {DESCRIPTION_MAX} characters"),
+            );
+        }
+
+        if value
+            .chars()
+            .any(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t'))
+        {
+            self.errors.add(field, "may not contain control characters");
+        }
+
+        self
+    }
+
+    /// Multi-line text that can't be left empty, such as comments.
+    pub fn body(self, field: &'static str, value: &str) -> Self {
+        let mut validator = self.description(field, value);
+
+        if value.trim().is_empty() {
+            validator.errors.add(field, "may not be empty");
+        }
+
+        validator
+    }
+
+    /// Scopes for a new key or token, which can't exceed those of `claims`.
+    pub fn scopes(mut self, field: &'static str, values: &[String], claims: &Claims) -> Self {
+        for value in values {
+            if !scope::is_known(value) {
+                self.errors.add(field, format!("unknown scope {value:?}"));
+            } else if !claims.has_scope(value) {
+                self.errors.add(
+                    field,
+                    format!("{value} is not granted to the current token"),
+                );
+            }
+        }
+
+        self
+    }
+
+    pub fn url(mut self, field: &'static str, value: &str) -> Self {
+        let valid = Url::parse(value).is_ok_and(|url| matches!(url.scheme(), "http" | "https"));
+
+        if !valid || value.len() > URL_MAX {
+            self.errors.add(
+                field,
+                format!("must be an http or https URL of at most {URL_MAX} characters"),
+            );
+        }
+
+        self
+    }
+
+    /// Only the format of IANA names such as `Europe/Berlin` is checked.
+    pub fn timezone(mut self, field: &'static str, value: &str) -> Self {
+        let valid = value == "UTC"
+            || value.len() <= LABEL_MAX
+                && value.split_once('/').is_some_and(|(area, location)| {
+                    area.chars().all(|c| c.is_ascii_alphabetic())
+                        && !area.is_empty()
+                        && !location.is_empty()
+                        && location.split('/').all(|part| {
+                            !part.is_empty()
+                                && part.chars().all(|c| {
+                                    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '+')
+                                })
+                        })
+                });
+
+        if !valid {
+            self.errors
+                .add(field, "must be UTC or a time zone such as Europe/Berlin");
+        }
+
+        self
+    }
+
+    /// Colors as `#rrggbb`.
+    pub fn color(mut self, field: &'static str, value: &str) -> Self {
+        let valid = value
+            .strip_prefix('#')
+            .is_some_and(|hex| hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()));
+
+        if !valid {
+            self.errors.add(field, "must be a color such as #1f6feb");
+        }
+
+        self
+    }
+
+    /// Optional numbers, such as lifetimes, that must be above zero if set.
+    pub fn positive(mut self, field: &'static str, value: Option<i64>) -> Self {
+        if value.is_some_and(|value| value <= 0) {
+            self.errors.add(field, "must be positive");
+        }
+
+        self
+    }
+
+    /// Names of enum variants as they appear in JSON, such as ticket statuses,
+    /// taken as strings so unknown names end up here rather than in the
+    /// extractor.
+    pub fn variant<T: DeserializeOwned>(
+        mut self,
+        field: &'static str,
+        value: Option<&str>,
+    ) -> Self {
+        if let Some(Err(e)) = value.map(deserialize_variant::<T>) {
+            // serde names the accepted variants after "expected".
+            let message = e.to_string();
+            let expected = message
+                .split_once(", expected ")
+                .map_or(message.as_str(), |(_, expected)| expected);
+
+            self.errors.add(field, format!("must be {expected}"));
+        }
+
+        self
+    }
+
+    pub fn finish(self) -> Result<()> {
+        match self.errors.is_empty() {
+            true => Ok(()),
+            false => Err(self.errors.into()),
+        }
+    }
+}
+
+/// The variant of `T` named `value`, if any. See [`Validator::variant`].
+pub fn parse_variant<T: DeserializeOwned>(value: &str) -> Option<T> {
+    deserialize_variant(value).ok()
+}
+
+fn deserialize_variant<T: DeserializeOwned>(value: &str) -> std::result::Result<T, value::Error> {
+    T::deserialize(value.into_deserializer())
+}
+
+/// Rough score from 0 (trivial) to 4 (strong) based on length, character
+/// variety and repetition. Passwords containing the username lose a point.
+pub fn password_strength(password: &str, username: &str) -> u8 {
+    let length = password.chars().count();
+
+    let classes = [
+        password.chars().any(|c| c.is_lowercase()),
+        password.chars().any(|c| c.is_uppercase()),
+        password.chars().any(|c| c.is_ascii_digit()),
+        password.chars().any(|c| !c.is_alphanumeric()),
+    ]
+    .into_iter()
+    .filter(|&class| class)
+    .count();
+
+    let unique = password.chars().collect::<HashSet<_>>().len();
+
+    let mut score = 0u8;
+
+    if length >= 10 {
+        score += 1;
+    }
+    if length >= 14 {
+        score += 1;
+    }
+    if classes >= 3 {
+        score += 1;
+    }
+    if classes == 4 || length >= 20 {
+        score += 1;
+    }
+
+    if unique * 2 < length {
+        score = score.saturating_sub(1);
+    }
+    if !username.is_empty() && password.to_lowercase().contains(&username.to_lowercase()) {
+        score = score.saturating_sub(1);
+    }
+
+    score.min(4)
+}
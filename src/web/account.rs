@@ -0,0 +1,523 @@
+use axum::{
+    Json, Router,
+    extract::State,
+    http::{StatusCode, header},
+    response::IntoResponse,
+    routing,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sqlx::{Pool, Sqlite};
+use tower_cookies::Cookies;
+use tracing::info;
+
+use crate::{
+    audit::{Entry, Event},
+    crypt::password,
+    error::{AuthError, DatabaseError, Result, UserError},
+    middleware::{
+        client::ClientInfo,
+        impersonation::forbid_impersonation,
+        jwt::Claims,
+        revocation::revocations,
+        role::Role,
+        scope::{AccountRead, AccountWrite, require_scope},
+        session,
+    },
+    validation::Validator,
+    web::{login, nullable},
+};
+
+pub fn router(pool: Pool<Sqlite>) -> Router {
+    Router::new()
+        .route(
+            "/account",
+            routing::get(get).route_layer(require_scope::<AccountRead>(&pool)),
+        )
+        .route(
+            "/account",
+            routing::patch(update)
+                .delete(delete)
+                .route_layer(require_scope::<AccountWrite>(&pool))
+                .route_layer(forbid_impersonation(&pool)),
+        )
+        .route(
+            "/account/export",
+            routing::get(export).route_layer(require_scope::<AccountRead>(&pool)),
+        )
+        .with_state(pool)
+}
+
+#[derive(Serialize)]
+struct Account {
+    username: String,
+    email: Option<String>,
+    display_name: Option<String>,
+    avatar_url: Option<String>,
+    timezone: Option<String>,
+    role: Role,
+    totp_enabled: bool,
+}
+
+async fn get(claims: Claims, State(pool): State<Pool<Sqlite>>) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle get /account", "Handler");
+
+    let account = find(&pool, &claims.sub).await?;
+
+    Ok((StatusCode::OK, Json(account)))
+}
+
+/// Fields that are left out stay as they are, `null` clears them.
+#[derive(Deserialize)]
+struct UpdatePayload {
+    #[serde(default, deserialize_with = "nullable")]
+    email: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    display_name: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    avatar_url: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    timezone: Option<Option<String>>,
+}
+
+async fn update(
+    claims: Claims,
+    State(pool): State<Pool<Sqlite>>,
+    Json(payload): Json<UpdatePayload>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle patch /account", "Handler");
+
+    let mut validator = Validator::new();
+
+    if let Some(Some(email)) = &payload.email {
+        validator = validator.email("email", email);
+    }
+    if let Some(Some(display_name)) = &payload.display_name {
+        validator = validator.label("display_name", display_name);
+    }
+    if let Some(Some(avatar_url)) = &payload.avatar_url {
+        validator = validator.url("avatar_url", avatar_url);
+    }
+    if let Some(Some(timezone)) = &payload.timezone {
+        validator = validator.timezone("timezone", timezone);
+    }
+
+    validator.finish()?;
+
+    let current = find(&pool, &claims.sub).await?;
+
+    let email = payload.email.unwrap_or(current.email);
+    let display_name = payload
+        .display_name
+        .unwrap_or(current.display_name)
+        .map(|name| name.trim().to_string());
+    let avatar_url = payload.avatar_url.unwrap_or(current.avatar_url);
+    let timezone = payload.timezone.unwrap_or(current.timezone);
+
+    let taken = sqlx::query!(
+        r#"
+        select username
+        from users
+        where email = ?
+        and username != ?
+        "#,
+        email,
+        claims.sub,
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?;
+
+    if taken.is_some() {
+        return Err(UserError::EmailTaken.into());
+    }
+
+    sqlx::query!(
+        r#"
+        update users
+        set email = ?, display_name = ?, avatar_url = ?, timezone = ?
+        where username = ?
+        "#,
+        email,
+        display_name,
+        avatar_url,
+        timezone,
+        claims.sub,
+    )
+    .execute(&pool)
+    .await
+    .map_err(|_| DatabaseError::UpdateFailed)?;
+
+    let account = find(&pool, &claims.sub).await?;
+
+    Ok((StatusCode::OK, Json(account)))
+}
+
+#[derive(Deserialize)]
+struct DeletePayload {
+    password: Option<String>,
+    /// Current authenticator code, asked instead of the password from
+    /// accounts that never chose one.
+    code: Option<String>,
+}
+
+/// Deletes the user and, through the foreign keys, everything that belongs
+/// to it. Tickets, comments and history stay without their reporter, author
+/// or actor. Chat state follows through the revocation broadcast. Accounts created through a login provider have no
+/// password to confirm with unless they set one through a reset.
+async fn delete(
+    claims: Claims,
+    client: ClientInfo,
+    cookies: Cookies,
+    State(pool): State<Pool<Sqlite>>,
+    Json(payload): Json<DeletePayload>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle delete /account", "Handler");
+
+    let user = sqlx::query!(
+        r#"
+        select password_hash, password_set as "password_set: bool"
+        from users
+        where username = ?
+        "#,
+        claims.sub,
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?
+    .ok_or(UserError::NotFound(claims.sub.clone()))?;
+
+    match user.password_set {
+        true => {
+            let password = payload.password.unwrap_or_default();
+
+            if !password::verify(password, user.password_hash).await? {
+                return Err(AuthError::WrongCredentials.into());
+            }
+        }
+        false if login::totp_enabled(&pool, &claims.sub).await? => {
+            let code = payload.code.unwrap_or_default();
+
+            if !login::verify_totp(&pool, &claims.sub, &code).await? {
+                return Err(AuthError::InvalidMfaCode.into());
+            }
+        }
+        false => {}
+    }
+
+    session::remove(&pool, &cookies).await?;
+
+    sqlx::query!(
+        r#"
+        delete from users
+        where username = ?
+        "#,
+        claims.sub,
+    )
+    .execute(&pool)
+    .await
+    .map_err(|_| DatabaseError::DeleteFailed)?;
+
+    revocations().revoke_token(&claims).await?;
+    revocations().remove_user(&claims.sub).await?;
+
+    Entry::new(Event::AccountDeleted, &client)
+        .by(&claims)
+        .record(&pool)
+        .await;
+
+    Ok((StatusCode::OK, Json(json!({ "username": claims.sub }))))
+}
+
+/// Tickets the user reported or is assigned to, including those in the trash.
+#[derive(Serialize)]
+struct ExportedTicket {
+    id: i64,
+    title: String,
+    description: String,
+    status: String,
+    priority: String,
+    reporter: Option<String>,
+    assignee: Option<String>,
+    created_at: i64,
+    updated_at: i64,
+    deleted_at: Option<i64>,
+    deleted_by: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ExportedComment {
+    id: i64,
+    ticket_id: i64,
+    body: String,
+    created_at: i64,
+    updated_at: i64,
+}
+
+/// Ticket changes made by the user.
+#[derive(Serialize)]
+struct ExportedChange {
+    id: i64,
+    ticket_id: i64,
+    action: String,
+    field: Option<String>,
+    comment_id: Option<i64>,
+    old_value: Option<Value>,
+    new_value: Option<Value>,
+    impersonator: Option<String>,
+    created_at: i64,
+}
+
+#[derive(Serialize)]
+struct ExportedSession {
+    created_at: i64,
+    expires_at: i64,
+}
+
+#[derive(Serialize)]
+struct ExportedTokenFamily {
+    family: String,
+    created_at: i64,
+    expires_at: i64,
+    revoked_at: Option<i64>,
+}
+
+#[derive(Serialize)]
+struct ExportedApiKey {
+    id: i64,
+    name: String,
+    prefix: String,
+    scopes: String,
+    created_at: i64,
+    expires_at: Option<i64>,
+    last_used_at: Option<i64>,
+    last_used_ip: Option<String>,
+    revoked_at: Option<i64>,
+}
+
+#[derive(Serialize)]
+struct ExportedIdentity {
+    issuer: String,
+    subject: String,
+    email: Option<String>,
+    created_at: i64,
+    last_login_at: i64,
+}
+
+#[derive(Serialize)]
+struct ExportedPasswordReset {
+    created_at: i64,
+    expires_at: i64,
+    used_at: Option<i64>,
+}
+
+/// Everything stored about the user as a JSON download. Secrets and their
+/// hashes are left out. Chat messages are only relayed and never stored, so
+/// there are none to export.
+async fn export(claims: Claims, State(pool): State<Pool<Sqlite>>) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle get /account/export", "Handler");
+
+    let account = find(&pool, &claims.sub).await?;
+
+    let tickets = sqlx::query_as!(
+        ExportedTicket,
+        r#"
+        select
+            id as "id!",
+            title,
+            description,
+            status,
+            priority,
+            reporter,
+            assignee,
+            created_at,
+            updated_at,
+            deleted_at,
+            deleted_by
+        from tickets
+        where reporter = ?1
+        or assignee = ?1
+        order by id
+        "#,
+        claims.sub,
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?;
+
+    let comments = sqlx::query_as!(
+        ExportedComment,
+        r#"
+        select id as "id!", ticket_id, body, created_at, updated_at
+        from ticket_comments
+        where author = ?
+        and deleted_at is null
+        order by id
+        "#,
+        claims.sub,
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?;
+
+    let changes = sqlx::query!(
+        r#"
+        select
+            id as "id!",
+            ticket_id,
+            action,
+            field,
+            comment_id,
+            old_value,
+            new_value,
+            impersonator,
+            created_at
+        from ticket_history
+        where actor = ?
+        order by id
+        "#,
+        claims.sub,
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?
+    .into_iter()
+    .map(|change| ExportedChange {
+        id: change.id,
+        ticket_id: change.ticket_id,
+        action: change.action,
+        field: change.field,
+        comment_id: change.comment_id,
+        old_value: change
+            .old_value
+            .and_then(|value| serde_json::from_str(&value).ok()),
+        new_value: change
+            .new_value
+            .and_then(|value| serde_json::from_str(&value).ok()),
+        impersonator: change.impersonator,
+        created_at: change.created_at,
+    })
+    .collect::<Vec<_>>();
+
+    let sessions = sqlx::query_as!(
+        ExportedSession,
+        r#"
+        select created_at, expires_at
+        from sessions
+        where username = ?
+        order by created_at
+        "#,
+        claims.sub,
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?;
+
+    let token_families = sqlx::query_as!(
+        ExportedTokenFamily,
+        r#"
+        select
+            family as "family!",
+            min(created_at) as "created_at!: i64",
+            max(expires_at) as "expires_at!: i64",
+            max(revoked_at) as "revoked_at: i64"
+        from refresh_tokens
+        where username = ?
+        group by family
+        order by 2
+        "#,
+        claims.sub,
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?;
+
+    let api_keys = sqlx::query_as!(
+        ExportedApiKey,
+        r#"
+        select
+            id, name, prefix, scopes, created_at, expires_at,
+            last_used_at, last_used_ip, revoked_at
+        from api_keys
+        where username = ?
+        order by id
+        "#,
+        claims.sub,
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?;
+
+    let identities = sqlx::query_as!(
+        ExportedIdentity,
+        r#"
+        select issuer, subject, email, created_at, last_login_at
+        from user_identities
+        where username = ?
+        "#,
+        claims.sub,
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?;
+
+    let password_resets = sqlx::query_as!(
+        ExportedPasswordReset,
+        r#"
+        select created_at, expires_at, used_at
+        from password_resets
+        where username = ?
+        order by created_at
+        "#,
+        claims.sub,
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?;
+
+    let body = json!({
+        "exported_at": Utc::now().timestamp(),
+        "account": account,
+        "tickets": tickets,
+        "comments": comments,
+        "ticket_history": changes,
+        "sessions": sessions,
+        "token_families": token_families,
+        "api_keys": api_keys,
+        "identities": identities,
+        "password_resets": password_resets,
+    });
+
+    let disposition = format!("attachment; filename=\"account-{}.json\"", claims.sub);
+
+    Ok((
+        StatusCode::OK,
+        [(header::CONTENT_DISPOSITION, disposition)],
+        Json(body),
+    ))
+}
+
+async fn find(pool: &Pool<Sqlite>, username: &str) -> Result<Account> {
+    sqlx::query_as!(
+        Account,
+        r#"
+        select
+            users.username,
+            users.email,
+            users.display_name,
+            users.avatar_url,
+            users.timezone,
+            users.role as "role: Role",
+            user_totp.confirmed_at is not null as "totp_enabled!: bool"
+        from users
+        left join user_totp on user_totp.username = users.username
+        where users.username = ?
+        "#,
+        username,
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?
+    .ok_or(UserError::NotFound(username.to_string()).into())
+}
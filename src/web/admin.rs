@@ -0,0 +1,361 @@
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing,
+};
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sqlx::{Pool, Sqlite};
+use tracing::info;
+
+use crate::{
+    audit::{Entry, Event},
+    config::config,
+    crypt::{keys::keys, token},
+    error::{AuthError, DatabaseError, Result, UserError},
+    middleware::{
+        client::ClientInfo,
+        jwt::{Actor, Claims},
+        revocation::revocations,
+        role::{Admin, Role, require_role},
+        scope::{AuditRead, UsersAdmin, require_scope},
+        throttle::throttle,
+    },
+    validation::Validator,
+    web::Sensitive,
+};
+
+/// Entries per page of the audit log, unless asked for fewer.
+const AUDIT_PAGE_MAX: i64 = 200;
+
+pub fn router(pool: Pool<Sqlite>) -> Router {
+    Router::new()
+        .route(
+            "/admin/users/{username}/role",
+            routing::put(set_role).route_layer(require_scope::<UsersAdmin>(&pool)),
+        )
+        .route(
+            "/admin/users/{username}/lock",
+            routing::delete(unlock).route_layer(require_scope::<UsersAdmin>(&pool)),
+        )
+        .route(
+            "/admin/impersonate",
+            routing::post(impersonate).route_layer(require_scope::<UsersAdmin>(&pool)),
+        )
+        .route(
+            "/admin/audit",
+            routing::get(audit).route_layer(require_scope::<AuditRead>(&pool)),
+        )
+        .route_layer(require_role::<Admin>(&pool))
+        .with_state(pool)
+}
+
+/// Makes the existing users named in `ADMIN_USERNAMES` admins. Run at
+/// startup, names that aren't registered are skipped rather than reserved,
+/// so nobody becomes admin by signing up under one of them.
+pub async fn promote_configured(pool: &Pool<Sqlite>) -> Result<()> {
+    for username in &config().admins {
+        let result = sqlx::query!(
+            r#"
+            update users
+            set role = ?
+            where username = ?
+            and role != ?
+            "#,
+            Role::Admin,
+            username,
+            Role::Admin,
+        )
+        .execute(pool)
+        .await
+        .map_err(|_| DatabaseError::UpdateFailed)?;
+
+        if result.rows_affected() == 0 {
+            continue;
+        }
+
+        info!("[{:^12}] ━ {} promoted to admin", "Admin", username);
+
+        revocations().revoke_user(username).await?;
+
+        Entry::new(Event::RoleChanged, &ClientInfo::default())
+            .target(username)
+            .details(json!({ "role": Role::Admin, "source": "ADMIN_USERNAMES" }))
+            .record(pool)
+            .await;
+    }
+
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct RolePayload {
+    role: Role,
+}
+
+async fn set_role(
+    claims: Claims,
+    client: ClientInfo,
+    State(pool): State<Pool<Sqlite>>,
+    Path(username): Path<String>,
+    Json(payload): Json<RolePayload>,
+) -> Result<impl IntoResponse> {
+    info!(
+        "[{:^12}] ┃ handle put /admin/users/{{username}}/role",
+        "Handler"
+    );
+
+    let result = sqlx::query!(
+        r#"
+        update users
+        set role = ?
+        where username = ?
+        "#,
+        payload.role,
+        username,
+    )
+    .execute(&pool)
+    .await
+    .map_err(|_| DatabaseError::UpdateFailed)?;
+
+    if result.rows_affected() == 0 {
+        return Err(UserError::NotFound(username).into());
+    }
+
+    // Roles are embedded in access tokens, make the user pick up the new one
+    // with their refresh token.
+    revocations().revoke_user(&username).await?;
+
+    Entry::new(Event::RoleChanged, &client)
+        .by(&claims)
+        .target(&username)
+        .details(json!({ "role": payload.role }))
+        .record(&pool)
+        .await;
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "username": username, "role": payload.role })),
+    ))
+}
+
+async fn unlock(
+    claims: Claims,
+    client: ClientInfo,
+    State(pool): State<Pool<Sqlite>>,
+    Path(username): Path<String>,
+) -> Result<impl IntoResponse> {
+    info!(
+        "[{:^12}] ┃ handle delete /admin/users/{{username}}/lock",
+        "Handler"
+    );
+
+    let unlocked = throttle().unlock(&username);
+
+    if unlocked {
+        Entry::new(Event::AccountUnlocked, &client)
+            .by(&claims)
+            .target(&username)
+            .record(&pool)
+            .await;
+    }
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "username": username, "unlocked": unlocked })),
+    ))
+}
+
+#[derive(Deserialize)]
+struct ImpersonatePayload {
+    username: String,
+    /// Seconds, at most `IMPERSONATION_TTL`.
+    expires_in: Option<i64>,
+    /// Kept in the audit log, e.g. the ticket being looked into.
+    reason: Option<String>,
+}
+
+/// Issues a short-lived access token for `username` that names the admin in
+/// its `act` claim. It comes without a refresh token and is refused by the
+/// routes that manage credentials.
+async fn impersonate(
+    claims: Claims,
+    client: ClientInfo,
+    State(pool): State<Pool<Sqlite>>,
+    Json(payload): Json<ImpersonatePayload>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle post /admin/impersonate", "Handler");
+
+    let mut validator = Validator::new().positive("expires_in", payload.expires_in);
+
+    if let Some(reason) = &payload.reason {
+        validator = validator.label("reason", reason);
+    }
+
+    validator.finish()?;
+
+    let user = sqlx::query!(
+        r#"
+        select role as "role: Role"
+        from users
+        where username = ?
+        "#,
+        payload.username,
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?
+    .ok_or(UserError::NotFound(payload.username.clone()))?;
+
+    // Another admin could do nothing the caller can't, but it would blur who
+    // acted in the audit log.
+    if payload.username == claims.sub || user.role == Role::Admin {
+        return Err(AuthError::Forbidden.into());
+    }
+
+    let ttl = config().token.impersonation_ttl;
+    let expires_in = payload.expires_in.unwrap_or(ttl).min(ttl);
+    let now = Utc::now();
+
+    let impersonation = Claims {
+        sub: payload.username.clone(),
+        exp: (now + Duration::seconds(expires_in)).timestamp() as usize,
+        iat: revocations()
+            .issued_at(&payload.username)
+            .max(revocations().issued_at(&claims.sub)) as usize,
+        jti: token::generate(),
+        role: user.role,
+        sid: None,
+        scope: None,
+        act: Some(Actor {
+            sub: claims.sub.clone(),
+        }),
+    };
+
+    let access_token = keys().encode(&impersonation)?;
+
+    Entry::new(Event::ImpersonationStarted, &client)
+        .by(&claims)
+        .target(&payload.username)
+        .details(json!({ "expires_in": expires_in, "reason": payload.reason }))
+        .record(&pool)
+        .await;
+
+    Ok((
+        StatusCode::OK,
+        Extension(Sensitive),
+        Json(json!({
+            "access_token": access_token,
+            "token_type": "Bearer",
+            "expires_in": expires_in,
+            "username": payload.username,
+        })),
+    ))
+}
+
+#[derive(Deserialize)]
+struct AuditQuery {
+    event: Option<Event>,
+    actor: Option<String>,
+    /// Admin that acted while impersonating `actor`.
+    impersonator: Option<String>,
+    target: Option<String>,
+    /// Unix timestamps, `since` inclusive and `until` exclusive.
+    since: Option<i64>,
+    until: Option<i64>,
+    /// `next_cursor` of the previous page.
+    cursor: Option<i64>,
+    limit: Option<i64>,
+}
+
+#[derive(Serialize)]
+struct AuditEntry {
+    id: i64,
+    event: Event,
+    actor: Option<String>,
+    impersonator: Option<String>,
+    target: Option<String>,
+    ip: Option<String>,
+    user_agent: Option<String>,
+    details: Option<Value>,
+    created_at: i64,
+}
+
+/// Newest entries first. Pages are chained through the id of their last
+/// entry, so entries written meanwhile don't shift them.
+async fn audit(
+    State(pool): State<Pool<Sqlite>>,
+    Query(query): Query<AuditQuery>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle get /admin/audit", "Handler");
+
+    let limit = query.limit.unwrap_or(50).clamp(1, AUDIT_PAGE_MAX);
+    let fetch = limit + 1;
+
+    let mut entries = sqlx::query!(
+        r#"
+        select
+            id as "id!",
+            event as "event!: Event",
+            actor,
+            impersonator,
+            target,
+            ip,
+            user_agent,
+            details,
+            created_at
+        from audit_log
+        where (?1 is null or event = ?1)
+        and (?2 is null or actor = ?2)
+        and (?3 is null or impersonator = ?3)
+        and (?4 is null or target = ?4)
+        and (?5 is null or created_at >= ?5)
+        and (?6 is null or created_at < ?6)
+        and (?7 is null or id < ?7)
+        order by id desc
+        limit ?8
+        "#,
+        query.event,
+        query.actor,
+        query.impersonator,
+        query.target,
+        query.since,
+        query.until,
+        query.cursor,
+        fetch,
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?
+    .into_iter()
+    .map(|entry| AuditEntry {
+        id: entry.id,
+        event: entry.event,
+        actor: entry.actor,
+        impersonator: entry.impersonator,
+        target: entry.target,
+        ip: entry.ip,
+        user_agent: entry.user_agent,
+        details: entry
+            .details
+            .and_then(|details| serde_json::from_str(&details).ok()),
+        created_at: entry.created_at,
+    })
+    .collect::<Vec<_>>();
+
+    let next_cursor = match entries.len() as i64 > limit {
+        true => {
+            entries.truncate(limit as usize);
+            entries.last().map(|entry| entry.id)
+        }
+        false => None,
+    };
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "items": entries, "next_cursor": next_cursor })),
+    ))
+}
@@ -0,0 +1,248 @@
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::{Pool, Sqlite};
+use tracing::info;
+
+use crate::{
+    audit::{Entry, Event},
+    crypt::token,
+    error::{ApiKeyError, DatabaseError, Result},
+    middleware::{
+        api_key,
+        client::ClientInfo,
+        impersonation::forbid_impersonation,
+        jwt::Claims,
+        scope::{AccountRead, AccountWrite, require_scope},
+    },
+    validation::Validator,
+    web::Sensitive,
+};
+
+/// Characters of a key kept in clear text, so users can tell keys apart.
+const PREFIX_LEN: usize = 12;
+
+pub fn router(pool: Pool<Sqlite>) -> Router {
+    Router::new()
+        .route(
+            "/account/api-keys",
+            routing::post(create)
+                .route_layer(require_scope::<AccountWrite>(&pool))
+                .route_layer(forbid_impersonation(&pool)),
+        )
+        .route(
+            "/account/api-keys",
+            routing::get(list).route_layer(require_scope::<AccountRead>(&pool)),
+        )
+        .route(
+            "/account/api-keys/{id}",
+            routing::delete(revoke).route_layer(require_scope::<AccountWrite>(&pool)),
+        )
+        .with_state(pool)
+}
+
+#[derive(Deserialize)]
+struct CreatePayload {
+    name: String,
+    /// Seconds until the key expires, keys without one never expire.
+    expires_in: Option<i64>,
+    #[serde(default)]
+    scopes: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct ApiKeyInfo {
+    id: i64,
+    name: String,
+    prefix: String,
+    scopes: Vec<String>,
+    created_at: i64,
+    expires_at: Option<i64>,
+    last_used_at: Option<i64>,
+    last_used_ip: Option<String>,
+}
+
+async fn create(
+    claims: Claims,
+    client: ClientInfo,
+    State(pool): State<Pool<Sqlite>>,
+    Json(payload): Json<CreatePayload>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle post /account/api-keys", "Handler");
+
+    Validator::new()
+        .label("name", &payload.name)
+        .scopes("scopes", &payload.scopes, &claims)
+        .positive("expires_in", payload.expires_in)
+        .finish()?;
+
+    let key = api_key::generate();
+    let key_hash = token::hash(&key);
+    let prefix = key[..PREFIX_LEN].to_string();
+    let name = payload.name.trim().to_string();
+    let scopes = claims.narrow_scope(&payload.scopes).unwrap_or_default();
+    let created_at = Utc::now().timestamp();
+    let expires_at = payload.expires_in.map(|seconds| created_at + seconds);
+
+    let id = sqlx::query!(
+        r#"
+        insert into api_keys (username, name, prefix, key_hash, scopes, created_at, expires_at)
+        values (?, ?, ?, ?, ?, ?, ?)
+        "#,
+        claims.sub,
+        name,
+        prefix,
+        key_hash,
+        scopes,
+        created_at,
+        expires_at,
+    )
+    .execute(&pool)
+    .await
+    .map_err(|_| DatabaseError::InsertFailed)?
+    .last_insert_rowid();
+
+    Entry::new(Event::ApiKeyCreated, &client)
+        .by(&claims)
+        .target(id)
+        .details(json!({ "name": name, "scopes": scopes }))
+        .record(&pool)
+        .await;
+
+    // The key itself is only ever part of this response.
+    Ok((
+        StatusCode::CREATED,
+        Extension(Sensitive),
+        Json(json!({
+            "id": id,
+            "name": name,
+            "key": key,
+            "prefix": prefix,
+            "scopes": scopes.split_whitespace().collect::<Vec<_>>(),
+            "created_at": created_at,
+            "expires_at": expires_at,
+        })),
+    ))
+}
+
+async fn list(claims: Claims, State(pool): State<Pool<Sqlite>>) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle get /account/api-keys", "Handler");
+
+    let keys = sqlx::query!(
+        r#"
+        select id, name, prefix, scopes, created_at, expires_at, last_used_at, last_used_ip
+        from api_keys
+        where username = ?
+        and revoked_at is null
+        order by id
+        "#,
+        claims.sub,
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?
+    .into_iter()
+    .map(|key| ApiKeyInfo {
+        id: key.id,
+        name: key.name,
+        prefix: key.prefix,
+        scopes: key.scopes.split_whitespace().map(String::from).collect(),
+        created_at: key.created_at,
+        expires_at: key.expires_at,
+        last_used_at: key.last_used_at,
+        last_used_ip: key.last_used_ip,
+    })
+    .collect::<Vec<_>>();
+
+    Ok((StatusCode::OK, Json(keys)))
+}
+
+async fn revoke(
+    claims: Claims,
+    client: ClientInfo,
+    State(pool): State<Pool<Sqlite>>,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse> {
+    info!(
+        "[{:^12}] ┃ handle delete /account/api-keys/{{id}}",
+        "Handler"
+    );
+
+    let now = Utc::now().timestamp();
+
+    let result = sqlx::query!(
+        r#"
+        update api_keys
+        set revoked_at = ?
+        where id = ?
+        and username = ?
+        and revoked_at is null
+        "#,
+        now,
+        id,
+        claims.sub,
+    )
+    .execute(&pool)
+    .await
+    .map_err(|_| DatabaseError::UpdateFailed)?;
+
+    if result.rows_affected() == 0 {
+        return Err(ApiKeyError::NotFound(id).into());
+    }
+
+    Entry::new(Event::ApiKeyRevoked, &client)
+        .by(&claims)
+        .target(id)
+        .record(&pool)
+        .await;
+
+    Ok((StatusCode::OK, Json(json!({ "id": id }))))
+}
+
+/// Revokes every key of `username`, returning how many were still working.
+pub async fn revoke_user(pool: &Pool<Sqlite>, username: &str) -> Result<u64> {
+    let now = Utc::now().timestamp();
+
+    let result = sqlx::query!(
+        r#"
+        update api_keys
+        set revoked_at = ?
+        where username = ?
+        and revoked_at is null
+        "#,
+        now,
+        username,
+    )
+    .execute(pool)
+    .await
+    .map_err(|_| DatabaseError::UpdateFailed)?;
+
+    Ok(result.rows_affected())
+}
+
+/// Keys of `username` that are neither revoked nor expired.
+pub async fn count_active(pool: &Pool<Sqlite>, username: &str) -> Result<i64> {
+    let now = Utc::now().timestamp();
+
+    sqlx::query_scalar!(
+        r#"
+        select count(*)
+        from api_keys
+        where username = ?
+        and revoked_at is null
+        and (expires_at is null or expires_at > ?)
+        "#,
+        username,
+        now,
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed.into())
+}
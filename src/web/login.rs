@@ -1,18 +1,35 @@
-use axum::{Json, Router, extract::State, http::StatusCode, response::IntoResponse, routing};
+use std::net::{IpAddr, SocketAddr};
+
+use axum::{
+    Extension, Json, Router,
+    extract::{ConnectInfo, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing,
+};
 use chrono::{Duration, Utc};
-use jsonwebtoken::{EncodingKey, Header};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use sqlx::{Pool, Sqlite};
-use tower_cookies::{Cookie, Cookies};
-use tracing::info;
+use tower_cookies::Cookies;
+use tracing::{info, warn};
 
 use crate::{
-    error::{AuthError, Result},
-    middleware::jwt::{AuthBody, Claims},
+    audit::{Entry, Event},
+    config::config,
+    crypt::{keys::keys, password, token, totp},
+    error::{AuthError, DatabaseError, Result},
+    middleware::{client::ClientInfo, jwt::AuthBody, session, throttle::throttle},
+    web::{Sensitive, token as tokens},
 };
 
+const MFA_AUDIENCE: &str = "mfa";
+
 pub fn router(pool: Pool<Sqlite>) -> Router {
-    Router::new().route("/login", routing::post(login).with_state(pool))
+    Router::new()
+        .route("/login", routing::post(login))
+        .route("/login/mfa", routing::post(login_mfa))
+        .with_state(pool)
 }
 
 #[derive(Serialize, Deserialize)]
@@ -23,38 +40,322 @@ struct LoginPayload {
 
 async fn login(
     cookies: Cookies,
+    client: ClientInfo,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     State(pool): State<Pool<Sqlite>>,
     Json(payload): Json<LoginPayload>,
-) -> Result<impl IntoResponse> {
+) -> Result<Response> {
     info!("[{:^12}] ┃ handle post /login", "Handler");
 
-    sqlx::query!(
+    let ip = addr.ip();
+
+    check_throttle(&pool, &client, ip, &payload.username).await?;
+
+    let user = sqlx::query!(
         r#"
-        select *
+        select username, password_hash
         from users
         where username = ?
-        and password = ?
         "#,
         payload.username,
-        payload.password,
     )
-    .fetch_one(&pool)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?;
+
+    let Some(user) = user else {
+        password::verify_dummy(payload.password).await?;
+        throttle().failed(ip, &payload.username);
+
+        Entry::new(Event::LoginFailed, &client)
+            .actor(&payload.username)
+            .details(json!({ "reason": "unknown_user" }))
+            .record(&pool)
+            .await;
+
+        return Err(AuthError::WrongCredentials.into());
+    };
+
+    if !password::verify(payload.password.clone(), user.password_hash.clone()).await? {
+        throttle().failed(ip, &payload.username);
+
+        Entry::new(Event::LoginFailed, &client)
+            .actor(&payload.username)
+            .details(json!({ "reason": "wrong_password" }))
+            .record(&pool)
+            .await;
+
+        return Err(AuthError::WrongCredentials.into());
+    }
+
+    throttle().succeeded(&user.username);
+
+    if password::needs_rehash(&user.password_hash) {
+        rehash(&pool, &user.username, payload.password).await;
+    }
+
+    proceed(&pool, &cookies, &client, &user.username, "password").await
+}
+
+/// Ends the first step of a login. Users with a confirmed authenticator get
+/// an `mfa_token` to finish at `/login/mfa`, everyone else is logged in.
+/// `method` names the first factor in the audit log.
+pub async fn proceed(
+    pool: &Pool<Sqlite>,
+    cookies: &Cookies,
+    client: &ClientInfo,
+    username: &str,
+    method: &str,
+) -> Result<Response> {
+    if totp_enabled(pool, username).await? {
+        let body = MfaBody::new(username, method)?;
+
+        return Ok((StatusCode::OK, Extension(Sensitive), Json(body)).into_response());
+    }
+
+    let body = complete(pool, cookies, client, username, method).await?;
+
+    Ok((StatusCode::OK, Extension(Sensitive), Json(body)).into_response())
+}
+
+/// Short-lived token proving the password was correct. Its audience keeps
+/// it from being accepted anywhere a full token is expected.
+#[derive(Serialize, Deserialize)]
+struct MfaClaims {
+    sub: String,
+    aud: String,
+    exp: usize,
+    iat: usize,
+    /// First factor, for the audit log once the second one is given.
+    #[serde(default = "password_method")]
+    method: String,
+}
+
+fn password_method() -> String {
+    "password".into()
+}
+
+#[derive(Serialize)]
+struct MfaBody {
+    mfa_token: String,
+    token_type: String,
+    expires_in: i64,
+}
+
+impl MfaBody {
+    fn new(username: &str, method: &str) -> Result<Self> {
+        let ttl = config().mfa.pending_ttl;
+        let now = Utc::now();
+
+        let claims = MfaClaims {
+            sub: username.to_string(),
+            aud: MFA_AUDIENCE.into(),
+            exp: (now + Duration::seconds(ttl)).timestamp() as usize,
+            iat: now.timestamp() as usize,
+            method: method.to_string(),
+        };
+
+        Ok(Self {
+            mfa_token: keys().encode(&claims)?,
+            token_type: "MfaPending".into(),
+            expires_in: ttl,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct MfaPayload {
+    mfa_token: String,
+    /// Either the current code of the authenticator or a recovery code.
+    code: String,
+}
+
+async fn login_mfa(
+    cookies: Cookies,
+    client: ClientInfo,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(pool): State<Pool<Sqlite>>,
+    Json(payload): Json<MfaPayload>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle post /login/mfa", "Handler");
+
+    let claims = keys().decode_audience::<MfaClaims>(&payload.mfa_token, MFA_AUDIENCE)?;
+    let ip = addr.ip();
+
+    check_throttle(&pool, &client, ip, &claims.sub).await?;
+
+    let valid = match verify_totp(&pool, &claims.sub, &payload.code).await? {
+        true => true,
+        false => use_recovery_code(&pool, &claims.sub, &payload.code).await?,
+    };
+
+    if !valid {
+        throttle().failed(ip, &claims.sub);
+
+        Entry::new(Event::MfaFailed, &client)
+            .actor(&claims.sub)
+            .record(&pool)
+            .await;
+
+        return Err(AuthError::InvalidMfaCode.into());
+    }
+
+    throttle().succeeded(&claims.sub);
+
+    let method = format!("{}+totp", claims.method);
+    let body = complete(&pool, &cookies, &client, &claims.sub, &method).await?;
+
+    Ok((StatusCode::OK, Extension(Sensitive), Json(body)))
+}
+
+/// Final step of every login, starts the browser session and a token family.
+/// `method` names how the user authenticated in the audit log.
+async fn complete(
+    pool: &Pool<Sqlite>,
+    cookies: &Cookies,
+    client: &ClientInfo,
+    username: &str,
+    method: &str,
+) -> Result<AuthBody> {
+    session::create(pool, cookies, username).await?;
+
+    let body = tokens::issue(pool, username, None).await?;
+
+    Entry::new(Event::LoginSucceeded, client)
+        .actor(username)
+        .details(json!({ "method": method }))
+        .record(pool)
+        .await;
+
+    Ok(body)
+}
+
+pub async fn check_throttle(
+    pool: &Pool<Sqlite>,
+    client: &ClientInfo,
+    ip: IpAddr,
+    username: &str,
+) -> Result<()> {
+    let result = throttle().check(ip, username);
+
+    if result.is_err() {
+        Entry::new(Event::LoginBlocked, client)
+            .actor(username)
+            .record(pool)
+            .await;
+    }
+
+    result
+}
+
+pub async fn totp_enabled(pool: &Pool<Sqlite>, username: &str) -> Result<bool> {
+    let totp = sqlx::query!(
+        r#"
+        select username
+        from user_totp
+        where username = ?
+        and confirmed_at is not null
+        "#,
+        username,
+    )
+    .fetch_optional(pool)
     .await
-    .map_err(|_| AuthError::WrongCredentials)?;
+    .map_err(|_| DatabaseError::SelectFailed)?;
 
-    cookies.add(Cookie::new("user", payload.username.clone()));
+    Ok(totp.is_some())
+}
 
-    let claims = Claims {
-        sub: payload.username.clone(),
-        exp: (Utc::now() + Duration::days(1)).timestamp() as usize,
+/// Checks `code` against the confirmed secret of `username`. Each time step
+/// is accepted only once, so an observed code can't be replayed.
+pub async fn verify_totp(pool: &Pool<Sqlite>, username: &str, code: &str) -> Result<bool> {
+    let stored = sqlx::query!(
+        r#"
+        select secret
+        from user_totp
+        where username = ?
+        and confirmed_at is not null
+        "#,
+        username,
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?
+    .ok_or(AuthError::MfaNotEnrolled)?;
+
+    let Some(step) = totp::verify(&stored.secret, username, code)? else {
+        return Ok(false);
     };
 
-    let token = jsonwebtoken::encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(b"secret"),
+    let result = sqlx::query!(
+        r#"
+        update user_totp
+        set last_used_step = ?
+        where username = ?
+        and last_used_step < ?
+        "#,
+        step,
+        username,
+        step,
+    )
+    .execute(pool)
+    .await
+    .map_err(|_| DatabaseError::UpdateFailed)?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+async fn use_recovery_code(pool: &Pool<Sqlite>, username: &str, code: &str) -> Result<bool> {
+    let code_hash = token::hash(&totp::normalize_recovery_code(code));
+    let now = Utc::now().timestamp();
+
+    let result = sqlx::query!(
+        r#"
+        update recovery_codes
+        set used_at = ?
+        where code_hash = ?
+        and username = ?
+        and used_at is null
+        "#,
+        now,
+        code_hash,
+        username,
     )
-    .map_err(|_| AuthError::InvalidToken)?;
+    .execute(pool)
+    .await
+    .map_err(|_| DatabaseError::UpdateFailed)?;
+
+    if result.rows_affected() > 0 {
+        warn!("[{:^12}] ┃ recovery code used by {username}", "Handler");
+    }
+
+    Ok(result.rows_affected() > 0)
+}
+
+async fn rehash(pool: &Pool<Sqlite>, username: &str, password: String) {
+    let result = async {
+        let password_hash = password::hash(password).await?;
+
+        sqlx::query!(
+            r#"
+            update users
+            set password_hash = ?
+            where username = ?
+            "#,
+            password_hash,
+            username,
+        )
+        .execute(pool)
+        .await
+        .map_err(|_| DatabaseError::UpdateFailed)?;
+
+        Result::Ok(())
+    }
+    .await;
 
-    Ok((StatusCode::OK, Json(AuthBody::new(token))))
+    if let Err(e) = result {
+        warn!(
+            "[{:^12}] ┃ rehash password for {username} failed: {e:?}",
+            "Handler"
+        );
+    }
 }
@@ -0,0 +1,77 @@
+use axum::{Json, Router, extract::State, http::StatusCode, response::IntoResponse, routing};
+use serde_json::json;
+use sqlx::{Pool, Sqlite};
+use tower_cookies::Cookies;
+use tracing::info;
+
+use crate::{
+    audit::{Entry, Event},
+    error::Result,
+    middleware::{
+        client::ClientInfo, impersonation::forbid_impersonation, jwt::Claims,
+        revocation::revocations, session,
+    },
+    web::{api_key, token},
+};
+
+pub fn router(pool: Pool<Sqlite>) -> Router {
+    Router::new()
+        .route("/logout", routing::post(logout))
+        .route(
+            "/logout/all",
+            routing::post(logout_all).route_layer(forbid_impersonation(&pool)),
+        )
+        .with_state(pool)
+}
+
+async fn logout(
+    claims: Claims,
+    client: ClientInfo,
+    cookies: Cookies,
+    State(pool): State<Pool<Sqlite>>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle post /logout", "Handler");
+
+    revocations().revoke_token(&claims).await?;
+
+    if let Some(family) = &claims.sid {
+        token::revoke_family(&pool, family).await?;
+    }
+
+    session::remove(&pool, &cookies).await?;
+
+    Entry::new(Event::Logout, &client)
+        .by(&claims)
+        .record(&pool)
+        .await;
+
+    Ok((StatusCode::OK, Json(json!({ "username": claims.sub }))))
+}
+
+async fn logout_all(
+    claims: Claims,
+    client: ClientInfo,
+    cookies: Cookies,
+    State(pool): State<Pool<Sqlite>>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle post /logout/all", "Handler");
+
+    revocations().revoke_user(&claims.sub).await?;
+    token::revoke_user(&pool, &claims.sub).await?;
+    session::remove_user(&pool, &claims.sub).await?;
+    session::remove(&pool, &cookies).await?;
+
+    Entry::new(Event::LogoutAll, &client)
+        .by(&claims)
+        .record(&pool)
+        .await;
+
+    // API keys are meant for scripts that outlive logins and keep working.
+    // The count tells clients whether there are any left to revoke.
+    let api_keys_active = api_key::count_active(&pool, &claims.sub).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "username": claims.sub, "api_keys_active": api_keys_active })),
+    ))
+}
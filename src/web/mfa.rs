@@ -0,0 +1,230 @@
+use axum::{
+    Extension, Json, Router, extract::State, http::StatusCode, response::IntoResponse, routing,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::{Pool, Sqlite};
+use tracing::info;
+
+use crate::{
+    audit::{Entry, Event},
+    crypt::{password, token, totp},
+    error::{AuthError, DatabaseError, Result, UserError},
+    middleware::{
+        client::ClientInfo,
+        impersonation::forbid_impersonation,
+        jwt::Claims,
+        scope::{AccountWrite, require_scope},
+    },
+    web::Sensitive,
+};
+
+pub fn router(pool: Pool<Sqlite>) -> Router {
+    Router::new()
+        .route("/account/totp", routing::post(enroll).delete(disable))
+        .route("/account/totp/confirm", routing::post(confirm))
+        .route_layer(require_scope::<AccountWrite>(&pool))
+        .route_layer(forbid_impersonation(&pool))
+        .with_state(pool)
+}
+
+/// Starts enrollment with a fresh secret. It only takes effect once a code
+/// generated from it is confirmed, so an abandoned enrollment can't lock
+/// anyone out.
+async fn enroll(claims: Claims, State(pool): State<Pool<Sqlite>>) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle post /account/totp", "Handler");
+
+    let secret = totp::generate_secret(&claims.sub)?;
+    let uri = totp::uri(&secret, &claims.sub)?;
+
+    let result = sqlx::query!(
+        r#"
+        insert into user_totp (username, secret)
+        values (?, ?)
+        on conflict (username) do update
+        set secret = excluded.secret, last_used_step = 0
+        where user_totp.confirmed_at is null
+        "#,
+        claims.sub,
+        secret,
+    )
+    .execute(&pool)
+    .await
+    .map_err(|_| DatabaseError::InsertFailed)?;
+
+    if result.rows_affected() == 0 {
+        return Err(AuthError::MfaAlreadyEnabled.into());
+    }
+
+    Ok((
+        StatusCode::CREATED,
+        Extension(Sensitive),
+        Json(json!({ "secret": secret, "otpauth_uri": uri })),
+    ))
+}
+
+#[derive(Deserialize)]
+struct ConfirmPayload {
+    code: String,
+}
+
+async fn confirm(
+    claims: Claims,
+    client: ClientInfo,
+    State(pool): State<Pool<Sqlite>>,
+    Json(payload): Json<ConfirmPayload>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle post /account/totp/confirm", "Handler");
+
+    let pending = sqlx::query!(
+        r#"
+        select secret, confirmed_at
+        from user_totp
+        where username = ?
+        "#,
+        claims.sub,
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?
+    .ok_or(AuthError::MfaNotEnrolled)?;
+
+    if pending.confirmed_at.is_some() {
+        return Err(AuthError::MfaAlreadyEnabled.into());
+    }
+
+    let step = totp::verify(&pending.secret, &claims.sub, &payload.code)?
+        .ok_or(AuthError::InvalidMfaCode)?;
+    let now = Utc::now().timestamp();
+
+    let result = sqlx::query!(
+        r#"
+        update user_totp
+        set confirmed_at = ?, last_used_step = ?
+        where username = ?
+        and confirmed_at is null
+        "#,
+        now,
+        step,
+        claims.sub,
+    )
+    .execute(&pool)
+    .await
+    .map_err(|_| DatabaseError::UpdateFailed)?;
+
+    if result.rows_affected() == 0 {
+        return Err(AuthError::MfaAlreadyEnabled.into());
+    }
+
+    let recovery_codes = replace_recovery_codes(&pool, &claims.sub).await?;
+
+    Entry::new(Event::TotpEnabled, &client)
+        .by(&claims)
+        .record(&pool)
+        .await;
+
+    Ok((
+        StatusCode::OK,
+        Extension(Sensitive),
+        Json(json!({ "username": claims.sub, "recovery_codes": recovery_codes })),
+    ))
+}
+
+#[derive(Deserialize)]
+struct DisablePayload {
+    password: String,
+}
+
+async fn disable(
+    claims: Claims,
+    client: ClientInfo,
+    State(pool): State<Pool<Sqlite>>,
+    Json(payload): Json<DisablePayload>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle delete /account/totp", "Handler");
+
+    let user = sqlx::query!(
+        r#"
+        select password_hash
+        from users
+        where username = ?
+        "#,
+        claims.sub,
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?
+    .ok_or(UserError::NotFound(claims.sub.clone()))?;
+
+    if !password::verify(payload.password, user.password_hash).await? {
+        return Err(AuthError::WrongCredentials.into());
+    }
+
+    let result = sqlx::query!(
+        r#"
+        delete from user_totp
+        where username = ?
+        "#,
+        claims.sub,
+    )
+    .execute(&pool)
+    .await
+    .map_err(|_| DatabaseError::DeleteFailed)?;
+
+    if result.rows_affected() == 0 {
+        return Err(AuthError::MfaNotEnrolled.into());
+    }
+
+    sqlx::query!(
+        r#"
+        delete from recovery_codes
+        where username = ?
+        "#,
+        claims.sub,
+    )
+    .execute(&pool)
+    .await
+    .map_err(|_| DatabaseError::DeleteFailed)?;
+
+    Entry::new(Event::TotpDisabled, &client)
+        .by(&claims)
+        .record(&pool)
+        .await;
+
+    Ok((StatusCode::OK, Json(json!({ "username": claims.sub }))))
+}
+
+/// Only the hashes are stored, the codes are shown to the user once.
+async fn replace_recovery_codes(pool: &Pool<Sqlite>, username: &str) -> Result<Vec<String>> {
+    sqlx::query!(
+        r#"
+        delete from recovery_codes
+        where username = ?
+        "#,
+        username,
+    )
+    .execute(pool)
+    .await
+    .map_err(|_| DatabaseError::DeleteFailed)?;
+
+    let codes = totp::recovery_codes();
+
+    for code in &codes {
+        let code_hash = token::hash(&totp::normalize_recovery_code(code));
+
+        sqlx::query!(
+            r#"
+            insert into recovery_codes (code_hash, username)
+            values (?, ?)
+            "#,
+            code_hash,
+            username,
+        )
+        .execute(pool)
+        .await
+        .map_err(|_| DatabaseError::InsertFailed)?;
+    }
+
+    Ok(codes)
+}
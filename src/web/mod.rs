@@ -1,4 +1,30 @@
+use serde::{Deserialize, Deserializer};
+
+pub mod account;
+pub mod admin;
+pub mod api_key;
 pub mod login;
+pub mod logout;
+pub mod mfa;
+pub mod oidc;
+pub mod password;
 pub mod register;
 pub mod room;
 pub mod ticket;
+pub mod token;
+
+/// Response extension for bodies carrying credentials, such as tokens, API
+/// keys or recovery codes. The response log leaves them out.
+#[derive(Clone, Copy)]
+pub struct Sensitive;
+
+/// For `Option<Option<T>>` fields of partial updates, so that a missing field
+/// (`None`) can be told apart from an explicit `null` (`Some(None)`).
+/// Use together with `#[serde(default)]`.
+pub fn nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
@@ -0,0 +1,256 @@
+use axum::{
+    Router,
+    extract::{Query, State},
+    response::{IntoResponse, Redirect, Response},
+    routing,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::{Pool, Sqlite};
+use tower_cookies::{
+    Cookie, Cookies,
+    cookie::{SameSite, time},
+};
+use tracing::info;
+
+use crate::{
+    audit::{Entry, Event},
+    config::config,
+    crypt::{password, token},
+    error::{DatabaseError, OidcError, Result},
+    middleware::{client::ClientInfo, role::Role, session::Session},
+    oidc::{Identity, provider},
+    validation::Validator,
+    web::login,
+};
+
+const STATE_COOKIE: &str = "oidc_state";
+
+/// Attempts at finding a free username for a new user.
+const MAX_NAME_ATTEMPTS: usize = 100;
+
+pub fn router(pool: Pool<Sqlite>) -> Router {
+    Router::new()
+        .route("/oidc/login", routing::get(start))
+        .route("/oidc/callback", routing::get(callback))
+        .with_state(pool)
+}
+
+async fn start(cookies: Cookies) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle get /oidc/login", "Handler");
+
+    let authorization = provider()?.authorize().await?;
+
+    let cookie = Cookie::build((STATE_COOKIE, authorization.state))
+        .path("/oidc")
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .secure(config().session.secure)
+        .max_age(time::Duration::seconds(authorization.expires_in))
+        .build();
+
+    cookies.add(cookie);
+
+    Ok(Redirect::to(&authorization.url))
+}
+
+#[derive(Deserialize)]
+struct CallbackParams {
+    state: Option<String>,
+    code: Option<String>,
+    error: Option<String>,
+}
+
+/// A browser that is logged in links the identity to its user, which is the
+/// only way an identity gets attached to an existing account.
+async fn callback(
+    cookies: Cookies,
+    client: ClientInfo,
+    session: Result<Session>,
+    State(pool): State<Pool<Sqlite>>,
+    Query(params): Query<CallbackParams>,
+) -> Result<Response> {
+    info!("[{:^12}] ┃ handle get /oidc/callback", "Handler");
+
+    if let Some(error) = params.error {
+        return Err(OidcError::Denied(error).into());
+    }
+
+    // The state has to come back to the browser that started the login,
+    // otherwise anyone could log a victim into their own account.
+    let cookie_state = cookies.get(STATE_COOKIE).map(|c| c.value().to_string());
+    cookies.remove(Cookie::build(STATE_COOKIE).path("/oidc").build());
+
+    let (Some(state), Some(code)) = (params.state, params.code) else {
+        return Err(OidcError::InvalidState.into());
+    };
+
+    if cookie_state.as_deref() != Some(state.as_str()) {
+        return Err(OidcError::InvalidState.into());
+    }
+
+    let identity = provider()?.finish(&state, &code).await?;
+    let current = session.ok().map(|session| session.username);
+    let username = link(&pool, &client, &identity, current.as_deref()).await?;
+
+    // The provider only stands in for the password, a second factor is still
+    // asked for.
+    login::proceed(&pool, &cookies, &client, &username, "oidc").await
+}
+
+/// Finds the user an identity belongs to. Unknown identities are linked to
+/// `current`, the user logged in, or get a new user otherwise. They are
+/// never attached to an existing account by email, as that would hand the
+/// account to whoever controls the address at the provider.
+async fn link(
+    pool: &Pool<Sqlite>,
+    client: &ClientInfo,
+    identity: &Identity,
+    current: Option<&str>,
+) -> Result<String> {
+    let now = Utc::now().timestamp();
+
+    let linked = sqlx::query!(
+        r#"
+        update user_identities
+        set last_login_at = ?, email = ?
+        where issuer = ?
+        and subject = ?
+        returning username
+        "#,
+        now,
+        identity.email,
+        identity.issuer,
+        identity.subject,
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|_| DatabaseError::UpdateFailed)?;
+
+    if let Some(linked) = linked {
+        return Ok(linked.username);
+    }
+
+    let username = match current {
+        Some(username) => username.to_string(),
+        None => create_user(pool, identity).await?,
+    };
+
+    sqlx::query!(
+        r#"
+        insert into user_identities (issuer, subject, username, email, created_at, last_login_at)
+        values (?, ?, ?, ?, ?, ?)
+        "#,
+        identity.issuer,
+        identity.subject,
+        username,
+        identity.email,
+        now,
+        now,
+    )
+    .execute(pool)
+    .await
+    .map_err(|_| DatabaseError::InsertFailed)?;
+
+    info!(
+        "[{:^12}] ┃ linked {} at {} to {username}",
+        "Handler", identity.subject, identity.issuer
+    );
+
+    Entry::new(Event::IdentityLinked, client)
+        .actor(&username)
+        .details(json!({ "issuer": identity.issuer, "subject": identity.subject }))
+        .record(pool)
+        .await;
+
+    Ok(username)
+}
+
+/// New users get a random password nobody knows, they can set one through
+/// the reset flow. The email is only kept if the provider verified it and
+/// no other account uses it yet.
+async fn create_user(pool: &Pool<Sqlite>, identity: &Identity) -> Result<String> {
+    let email = identity.email.as_ref().filter(|_| identity.email_verified);
+
+    let taken = match email {
+        Some(email) => sqlx::query!(
+            r#"
+            select username
+            from users
+            where email = ?
+            "#,
+            email,
+        )
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| DatabaseError::SelectFailed)?
+        .is_some(),
+        None => false,
+    };
+
+    if taken {
+        return Err(OidcError::LinkRequired.into());
+    }
+
+    let password_hash = password::hash(token::generate()).await?;
+    let base = base_name(identity);
+
+    for attempt in 1..=MAX_NAME_ATTEMPTS {
+        let username = match attempt {
+            1 => base.clone(),
+            _ => format!("{base}-{attempt}"),
+        };
+
+        if Validator::new()
+            .name("username", &username)
+            .finish()
+            .is_err()
+        {
+            continue;
+        }
+
+        let result = sqlx::query!(
+            r#"
+            insert into users (username, password_hash, password_set, role, email)
+            values (?, ?, false, ?, ?)
+            on conflict do nothing
+            "#,
+            username,
+            password_hash,
+            Role::Member,
+            email,
+        )
+        .execute(pool)
+        .await
+        .map_err(|_| DatabaseError::InsertFailed)?;
+
+        if result.rows_affected() > 0 {
+            return Ok(username);
+        }
+    }
+
+    Err(DatabaseError::InsertFailed.into())
+}
+
+/// Username suggested by the provider, reduced to the characters usernames
+/// may contain and leaving room for a numeric suffix.
+fn base_name(identity: &Identity) -> String {
+    let max = config().validation.name_max.saturating_sub(4);
+
+    let name = identity
+        .preferred_username
+        .as_deref()
+        .or_else(|| identity.email.as_deref()?.split('@').next())
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        .skip_while(|c| !c.is_ascii_alphanumeric())
+        .take(max)
+        .collect::<String>();
+
+    match name.chars().count() < config().validation.name_min {
+        true => "user".into(),
+        false => name,
+    }
+}
@@ -0,0 +1,298 @@
+use std::net::SocketAddr;
+
+use axum::{
+    Json, Router,
+    extract::{ConnectInfo, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::{Pool, Sqlite};
+use tracing::{error, info};
+
+use crate::{
+    audit::{Entry, Event},
+    config::config,
+    crypt::{password, token},
+    error::{AuthError, DatabaseError, Result, UserError},
+    mail::{Mail, mailer},
+    middleware::{
+        client::ClientInfo,
+        impersonation::forbid_impersonation,
+        jwt::Claims,
+        revocation::revocations,
+        scope::{AccountWrite, require_scope},
+        session,
+        throttle::throttle,
+    },
+    validation::Validator,
+    web::{api_key, login, token as tokens},
+};
+
+pub fn router(pool: Pool<Sqlite>) -> Router {
+    Router::new()
+        .route(
+            "/account/password",
+            routing::post(change)
+                .route_layer(require_scope::<AccountWrite>(&pool))
+                .route_layer(forbid_impersonation(&pool)),
+        )
+        .route("/password/forgot", routing::post(forgot))
+        .route("/password/reset", routing::post(reset))
+        .with_state(pool)
+}
+
+#[derive(Deserialize)]
+struct ChangePayload {
+    current_password: String,
+    new_password: String,
+}
+
+async fn change(
+    claims: Claims,
+    client: ClientInfo,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(pool): State<Pool<Sqlite>>,
+    Json(payload): Json<ChangePayload>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle post /account/password", "Handler");
+
+    let ip = addr.ip();
+
+    // A stolen access token must not allow guessing the password faster than
+    // the login form does.
+    login::check_throttle(&pool, &client, ip, &claims.sub).await?;
+
+    let user = sqlx::query!(
+        r#"
+        select password_hash
+        from users
+        where username = ?
+        "#,
+        claims.sub,
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?
+    .ok_or(UserError::NotFound(claims.sub.clone()))?;
+
+    if !password::verify(payload.current_password, user.password_hash).await? {
+        throttle().failed(ip, &claims.sub);
+        return Err(AuthError::WrongCredentials.into());
+    }
+
+    throttle().succeeded(&claims.sub);
+
+    Validator::new()
+        .password("new_password", &payload.new_password, &claims.sub)
+        .finish()?;
+
+    let api_keys_revoked = set_password(&pool, &claims.sub, payload.new_password).await?;
+
+    Entry::new(Event::PasswordChanged, &client)
+        .by(&claims)
+        .details(json!({ "api_keys_revoked": api_keys_revoked }))
+        .record(&pool)
+        .await;
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "username": claims.sub, "api_keys_revoked": api_keys_revoked })),
+    ))
+}
+
+#[derive(Deserialize)]
+struct ForgotPayload {
+    email: String,
+}
+
+async fn forgot(
+    client: ClientInfo,
+    State(pool): State<Pool<Sqlite>>,
+    Json(payload): Json<ForgotPayload>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle post /password/forgot", "Handler");
+
+    let user = sqlx::query!(
+        r#"
+        select username
+        from users
+        where email = ?
+        "#,
+        payload.email,
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?;
+
+    // The response is the same whether or not the address is known, so this
+    // endpoint can't be used to find out who has an account.
+    if let Some(user) = user {
+        let mail = &config().mail;
+
+        let reset_token = token::generate();
+        let token_hash = token::hash(&reset_token);
+        let created_at = Utc::now().timestamp();
+        let expires_at = created_at + mail.reset_ttl;
+
+        sqlx::query!(
+            r#"
+            insert into password_resets (token_hash, username, created_at, expires_at)
+            values (?, ?, ?, ?)
+            "#,
+            token_hash,
+            user.username,
+            created_at,
+            expires_at,
+        )
+        .execute(&pool)
+        .await
+        .map_err(|_| DatabaseError::InsertFailed)?;
+
+        let mail = Mail {
+            to: payload.email,
+            subject: "Reset your password".into(),
+            body: format!(
+                "Hello {},\n\nUse the link below to choose a new password. \
+                It expires in {} minutes and can only be used once.\n\n{}{}\n\n\
+                If you didn't ask for this, you can ignore this mail.",
+                user.username,
+                mail.reset_ttl / 60,
+                mail.reset_url,
+                reset_token,
+            ),
+        };
+
+        // Sent in the background, waiting for the mail server would make
+        // known addresses answer slower than unknown ones.
+        let username = user.username.clone();
+        tokio::spawn(async move {
+            if let Err(e) = mailer().send(&mail).await {
+                error!("[{:^12}] ┃ reset mail for {}: {e:?}", "Handler", username);
+            }
+        });
+
+        Entry::new(Event::PasswordResetRequested, &client)
+            .target(&user.username)
+            .record(&pool)
+            .await;
+    }
+
+    Ok((
+        StatusCode::ACCEPTED,
+        "If the address belongs to an account, a reset link has been sent",
+    ))
+}
+
+#[derive(Deserialize)]
+struct ResetPayload {
+    token: String,
+    new_password: String,
+}
+
+async fn reset(
+    client: ClientInfo,
+    State(pool): State<Pool<Sqlite>>,
+    Json(payload): Json<ResetPayload>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle post /password/reset", "Handler");
+
+    let token_hash = token::hash(&payload.token);
+    let now = Utc::now().timestamp();
+
+    // Checked before the token is spent, so a rejected password can be retried.
+    let pending = sqlx::query!(
+        r#"
+        select username
+        from password_resets
+        where token_hash = ?
+        and used_at is null
+        and expires_at > ?
+        "#,
+        token_hash,
+        now,
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?
+    .ok_or(AuthError::InvalidResetToken)?;
+
+    Validator::new()
+        .password("new_password", &payload.new_password, &pending.username)
+        .finish()?;
+
+    let reset = sqlx::query!(
+        r#"
+        update password_resets
+        set used_at = ?
+        where token_hash = ?
+        and used_at is null
+        and expires_at > ?
+        returning username
+        "#,
+        now,
+        token_hash,
+        now,
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|_| DatabaseError::UpdateFailed)?
+    .ok_or(AuthError::InvalidResetToken)?;
+
+    sqlx::query!(
+        r#"
+        update password_resets
+        set used_at = ?
+        where username = ?
+        and used_at is null
+        "#,
+        now,
+        reset.username,
+    )
+    .execute(&pool)
+    .await
+    .map_err(|_| DatabaseError::UpdateFailed)?;
+
+    let api_keys_revoked = set_password(&pool, &reset.username, payload.new_password).await?;
+    throttle().unlock(&reset.username);
+
+    Entry::new(Event::PasswordReset, &client)
+        .target(&reset.username)
+        .details(json!({ "api_keys_revoked": api_keys_revoked }))
+        .record(&pool)
+        .await;
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "username": reset.username, "api_keys_revoked": api_keys_revoked })),
+    ))
+}
+
+/// Stores the new password and ends every session and API key of the user,
+/// so whoever knew the old password is locked out as well, including through
+/// keys they created with it. Returns the number of keys revoked.
+async fn set_password(pool: &Pool<Sqlite>, username: &str, new_password: String) -> Result<u64> {
+    let password_hash = password::hash(new_password).await?;
+
+    sqlx::query!(
+        r#"
+        update users
+        set password_hash = ?, password_set = true
+        where username = ?
+        "#,
+        password_hash,
+        username,
+    )
+    .execute(pool)
+    .await
+    .map_err(|_| DatabaseError::UpdateFailed)?;
+
+    revocations().revoke_user(username).await?;
+    tokens::revoke_user(pool, username).await?;
+    session::remove_user(pool, username).await?;
+
+    api_key::revoke_user(pool, username).await
+}
@@ -3,7 +3,12 @@ use serde::Deserialize;
 use sqlx::{Pool, Sqlite};
 use tracing::info;
 
-use crate::error::{DatabaseError, Result};
+use crate::{
+    crypt::password,
+    error::{DatabaseError, Result},
+    middleware::role::Role,
+    validation::Validator,
+};
 
 pub fn router(pool: Pool<Sqlite>) -> Router {
     Router::new()
@@ -15,6 +20,7 @@ pub fn router(pool: Pool<Sqlite>) -> Router {
 struct RegisterPayload {
     username: String,
     password: String,
+    email: Option<String>,
 }
 
 async fn register(
@@ -23,14 +29,32 @@ async fn register(
 ) -> Result<impl IntoResponse> {
     info!("[{:^12}] ┃ handle post /register", "Handler");
 
+    let mut validator = Validator::new()
+        .name("username", &payload.username)
+        .password("password", &payload.password, &payload.username);
+
+    if let Some(email) = &payload.email {
+        validator = validator.email("email", email);
+    }
+
+    validator.finish()?;
+
+    let password_hash = password::hash(payload.password).await?;
+
+    // Admins are only made by other admins or from `ADMIN_USERNAMES` at
+    // startup, never by signing up.
+    let role = Role::Member;
+
     let result = sqlx::query!(
         r#"
-        insert into users (username, password)
-        values (?, ?)
-        on conflict (username) do nothing
+        insert into users (username, password_hash, role, email)
+        values (?, ?, ?, ?)
+        on conflict do nothing
         "#,
         payload.username,
-        payload.password,
+        password_hash,
+        role,
+        payload.email,
     )
     .execute(&pool)
     .await
@@ -39,7 +63,7 @@ async fn register(
     match result.rows_affected() {
         0 => Ok((
             StatusCode::CONFLICT,
-            format!("User {} already exists", payload.username),
+            format!("User {} or email already exists", payload.username),
         )),
         _ => Ok((
             StatusCode::CREATED,
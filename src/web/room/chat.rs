@@ -14,14 +14,17 @@ use futures_util::{
     stream::{SplitSink, StreamExt},
 };
 use serde::{Deserialize, Serialize};
-use tokio::sync::broadcast::Sender;
+use tokio::sync::broadcast::{Sender, error::RecvError};
 use tracing::{error, info, warn};
 
 use super::{
     AppState,
-    message::{ChannelMessage, SocketMessage},
+    message::{Author, ChannelMessage, SocketMessage},
+};
+use crate::middleware::{
+    jwt::Claims,
+    revocation::{Revoked, revocations},
 };
-use crate::middleware::jwt::Claims;
 
 pub type Users = DashSet<Arc<User>>;
 pub type Rooms = DashSet<Arc<Room>>;
@@ -46,7 +49,7 @@ pub async fn chat(
     State(state): State<Arc<AppState>>,
 ) -> impl IntoResponse {
     ws.on_upgrade(move |socket| async move {
-        let username = claims.sub;
+        let username = claims.sub.clone();
 
         let Some(user) = state
             .users
@@ -66,6 +69,11 @@ pub async fn chat(
             return;
         };
 
+        let author = Author {
+            name: user.name.clone(),
+            actor: claims.actor().map(str::to_string),
+        };
+
         let (mut socket_tx, mut socket_rx) = socket.split();
         let mut channel_receiver = user.sender.subscribe();
 
@@ -74,6 +82,7 @@ pub async fn chat(
 
         let user_clone = user.clone();
         let state_clone = state.clone();
+        let author_clone = author.clone();
         let mut receive_task = tokio::spawn(async move {
             while let Some(Ok(message)) = socket_rx.next().await {
                 match message {
@@ -81,13 +90,19 @@ pub async fn chat(
                         socket_message_text_handler(
                             user_clone.clone(),
                             state_clone.clone(),
+                            &author_clone,
                             message.to_string(),
                         )
                         .await;
                     }
 
                     Message::Close(_) => {
-                        socket_message_close_handler(user_clone.clone(), state_clone.clone()).await
+                        socket_message_close_handler(
+                            user_clone.clone(),
+                            state_clone.clone(),
+                            &author_clone,
+                        )
+                        .await
                     }
 
                     _ => {}
@@ -101,17 +116,78 @@ pub async fn chat(
             }
         });
 
+        let mut revoked = revocations().subscribe();
+        let mut revoke_task = tokio::spawn(async move {
+            loop {
+                match revoked.recv().await {
+                    Ok(revoked) if revoked.applies_to(&claims) => break,
+                    Err(RecvError::Lagged(_)) if revocations().is_revoked(&claims) => break,
+                    Err(RecvError::Closed) => std::future::pending().await,
+                    _ => {}
+                }
+            }
+        });
+
         tokio::select! {
-            _ = &mut send_task => { receive_task.abort() },
-            _ = &mut receive_task => { send_task.abort() },
+            _ = &mut send_task => {},
+            _ = &mut receive_task => {},
+            _ = &mut revoke_task => {
+                info!("[{:^12}] ━ user {} token revoked", "WebSocket", user.name);
+                socket_message_close_handler(user.clone(), state.clone(), &author).await;
+            },
         };
 
+        send_task.abort();
+        receive_task.abort();
+        revoke_task.abort();
+
         state.connected_users.remove(&user);
         info!("[{:^12}] ━ user {} disconnect", "WebSocket", user.name);
     })
 }
 
-async fn socket_message_text_handler(user: Arc<User>, state: Arc<AppState>, message: String) {
+/// Removes a chat user along with its room memberships.
+pub fn remove_user(state: &AppState, name: &str) -> Option<Arc<User>> {
+    let user = state
+        .users
+        .iter()
+        .find(|user| user.name == name)
+        .map(|user| user.clone())?;
+
+    state.users.remove(&user);
+    state.user_rooms.remove(&user);
+    state.room_users.iter().for_each(|entry| {
+        entry.value().remove(&user);
+    });
+
+    Some(user)
+}
+
+/// Chat users are named after accounts, so the chat user of a deleted
+/// account is removed as well. Its socket is closed by the revocation.
+pub async fn remove_deleted_users(state: Arc<AppState>) {
+    let mut revoked = revocations().subscribe();
+
+    loop {
+        match revoked.recv().await {
+            Ok(Revoked::Deleted(username)) if remove_user(&state, &username).is_some() => {
+                info!("[{:^12}] ━ user {} removed", "WebSocket", username);
+            }
+            Err(RecvError::Lagged(missed)) => {
+                warn!("[{:^12}] ━ missed {} revocations", "WebSocket", missed);
+            }
+            Err(RecvError::Closed) => break,
+            _ => {}
+        }
+    }
+}
+
+async fn socket_message_text_handler(
+    user: Arc<User>,
+    state: Arc<AppState>,
+    author: &Author,
+    message: String,
+) {
     let Ok(message) = serde_json::from_str::<SocketMessage>(&message) else {
         error!("[{:^12}] ━ Invalid Message", "WebSocket");
         return;
@@ -146,7 +222,7 @@ async fn socket_message_text_handler(user: Arc<User>, state: Arc<AppState>, mess
 
             Arc::new(ChannelMessage {
                 room: room.clone(),
-                from: Some(user.name.clone()),
+                from: Some(author.clone()),
                 message: format!("user {} join room {}", user.name, room.name),
             })
         }
@@ -179,7 +255,7 @@ async fn socket_message_text_handler(user: Arc<User>, state: Arc<AppState>, mess
 
             Arc::new(ChannelMessage {
                 room: room.clone(),
-                from: Some(user.name.clone()),
+                from: Some(author.clone()),
                 message: format!("user {} leave room {}", user.name, room.name),
             })
         }
@@ -195,7 +271,7 @@ async fn socket_message_text_handler(user: Arc<User>, state: Arc<AppState>, mess
 
             Arc::new(ChannelMessage {
                 room,
-                from: Some(user.name.clone()),
+                from: Some(author.clone()),
                 message,
             })
         }
@@ -215,12 +291,12 @@ async fn socket_message_text_handler(user: Arc<User>, state: Arc<AppState>, mess
         });
 }
 
-async fn socket_message_close_handler(user: Arc<User>, state: Arc<AppState>) {
+async fn socket_message_close_handler(user: Arc<User>, state: Arc<AppState>, author: &Author) {
     state.user_rooms.entry(user.clone()).and_modify(|rooms| {
         rooms.iter().for_each(|room| {
             let message = Arc::new(ChannelMessage {
                 room: room.clone(),
-                from: Some(user.name.clone()),
+                from: Some(author.clone()),
                 message: format!("user {} leave room {}", user.name, room.name),
             });
 
@@ -1,7 +1,7 @@
 use std::{collections::HashMap, sync::Arc};
 
 use axum::{
-    Json,
+    Extension, Json,
     extract::{Query, State},
     http::StatusCode,
     response::IntoResponse,
@@ -14,10 +14,15 @@ use tracing::info;
 
 use super::{
     AppState,
-    chat::{Room, User},
+    chat::{self, Room, User},
     message::ChannelMessage,
 };
-use crate::error::{Result, RoomError};
+use crate::{
+    audit::{Entry, Event},
+    error::{Result, RoomError},
+    middleware::{client::ClientInfo, jwt::Claims},
+    validation::Validator,
+};
 
 #[derive(Deserialize)]
 pub struct CreateUserPayload {
@@ -30,6 +35,8 @@ pub async fn create_user(
 ) -> Result<impl IntoResponse> {
     info!("[{:^12}] ┃ handl post /chat/user", "Handler");
 
+    Validator::new().name("name", &payload.name).finish()?;
+
     let (sender, _) = broadcast::channel::<Arc<ChannelMessage>>(128);
 
     let user = Arc::new(User {
@@ -66,26 +73,20 @@ pub struct DeleteUserPayload {
 }
 
 pub async fn delete_user(
+    Extension(claims): Extension<Claims>,
+    client: ClientInfo,
     State(state): State<Arc<AppState>>,
     Query(payload): Query<DeleteUserPayload>,
 ) -> Result<impl IntoResponse> {
     info!("[{:^12}] ┃ handl delete /chat/user", "Handler");
 
-    let user = state
-        .users
-        .iter()
-        .find(|user| user.name == payload.name)
-        .ok_or(RoomError::UserNotFound)?
-        .clone();
+    let user = chat::remove_user(&state, &payload.name).ok_or(RoomError::UserNotFound)?;
 
-    state.users.remove(&user).ok_or(RoomError::UserNotFound)?;
-    state
-        .user_rooms
-        .remove(&user)
-        .ok_or(RoomError::UserNotFound)?;
-    state.room_users.iter().for_each(|entry| {
-        entry.value().remove(&user);
-    });
+    Entry::new(Event::ChatUserDeleted, &client)
+        .by(&claims)
+        .target(&user.name)
+        .record(&state.pool)
+        .await;
 
     Ok((StatusCode::OK, Json(json!({ "name": user.name }))))
 }
@@ -101,6 +102,8 @@ pub async fn create_room(
 ) -> Result<impl IntoResponse> {
     info!("[{:^12}] ┃ handl post /chat/room", "Handler");
 
+    Validator::new().name("name", &payload.name).finish()?;
+
     let room = Arc::new(Room { name: payload.name });
 
     state.rooms.insert(room.clone());
@@ -127,6 +130,8 @@ pub struct DeleteRoomPayload {
 }
 
 pub async fn delete_room(
+    Extension(claims): Extension<Claims>,
+    client: ClientInfo,
     State(state): State<Arc<AppState>>,
     Query(payload): Query<DeleteRoomPayload>,
 ) -> Result<impl IntoResponse> {
@@ -148,6 +153,12 @@ pub async fn delete_room(
         entry.value().remove(&room);
     });
 
+    Entry::new(Event::ChatRoomDeleted, &client)
+        .by(&claims)
+        .target(&room.name)
+        .record(&state.pool)
+        .await;
+
     Ok((StatusCode::OK, Json(room)))
 }
 
@@ -14,6 +14,16 @@ pub enum SocketMessage {
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
 pub struct ChannelMessage {
     pub room: Arc<Room>,
-    pub from: Option<String>,
+    /// Filled in by the server, whatever the client sends is ignored.
+    #[serde(default, skip_deserializing)]
+    pub from: Option<Author>,
     pub message: String,
 }
+
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct Author {
+    pub name: String,
+    /// Admin sending as `name` while impersonating it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actor: Option<String>,
+}
@@ -2,15 +2,20 @@ mod chat;
 mod manage;
 mod message;
 
-use std::sync::Arc;
+use std::{convert::Infallible, sync::Arc};
 
 use axum::{Router, middleware, routing};
+use sqlx::{Pool, Sqlite};
 
-use crate::middleware::jwt::Claims;
+use crate::middleware::{
+    jwt::Claims,
+    role::{Admin, Member, require_role},
+    scope::{ChatAdmin, ChatRead, ChatWrite, require_scope},
+};
 use chat::{ConnectedUsers, RoomUsers, Rooms, UserRooms, Users};
 
-#[derive(Default)]
 struct AppState {
+    pool: Pool<Sqlite>,
     users: Users,
     rooms: Rooms,
     connected_users: ConnectedUsers,
@@ -18,19 +23,67 @@ struct AppState {
     room_users: RoomUsers,
 }
 
-pub fn router() -> Router {
-    let state = Arc::new(AppState::default());
+pub fn router(pool: Pool<Sqlite>) -> Router {
+    let state = Arc::new(AppState {
+        pool: pool.clone(),
+        users: Users::default(),
+        rooms: Rooms::default(),
+        connected_users: ConnectedUsers::default(),
+        user_rooms: UserRooms::default(),
+        room_users: RoomUsers::default(),
+    });
+
+    tokio::spawn(chat::remove_deleted_users(state.clone()));
 
     Router::new()
-        .route("/chat", routing::any(chat::chat))
-        .route("/chat/user", routing::post(manage::create_user))
-        .route("/chat/user", routing::get(manage::list_user))
-        .route("/chat/user", routing::delete(manage::delete_user))
-        .route("/chat/room", routing::post(manage::create_room))
-        .route("/chat/room", routing::get(manage::list_rooms))
-        .route("/chat/room", routing::delete(manage::delete_room))
-        .route("/chat/user_rooms", routing::get(manage::list_user_rooms))
-        .route("/chat/room_users", routing::get(manage::list_room_users))
-        .route_layer(middleware::from_extractor::<Claims>())
+        .route(
+            "/chat",
+            routing::any(chat::chat)
+                .layer::<_, Infallible>(require_role::<Member>(&pool))
+                .layer(require_scope::<ChatWrite>(&pool)),
+        )
+        .route(
+            "/chat/user",
+            routing::post(manage::create_user)
+                .route_layer(require_role::<Member>(&pool))
+                .route_layer(require_scope::<ChatWrite>(&pool)),
+        )
+        .route(
+            "/chat/user",
+            routing::get(manage::list_user).route_layer(require_scope::<ChatRead>(&pool)),
+        )
+        .route(
+            "/chat/user",
+            routing::delete(manage::delete_user)
+                .route_layer(require_role::<Admin>(&pool))
+                .route_layer(require_scope::<ChatAdmin>(&pool)),
+        )
+        .route(
+            "/chat/room",
+            routing::post(manage::create_room)
+                .route_layer(require_role::<Member>(&pool))
+                .route_layer(require_scope::<ChatWrite>(&pool)),
+        )
+        .route(
+            "/chat/room",
+            routing::get(manage::list_rooms).route_layer(require_scope::<ChatRead>(&pool)),
+        )
+        .route(
+            "/chat/room",
+            routing::delete(manage::delete_room)
+                .route_layer(require_role::<Admin>(&pool))
+                .route_layer(require_scope::<ChatAdmin>(&pool)),
+        )
+        .route(
+            "/chat/user_rooms",
+            routing::get(manage::list_user_rooms).route_layer(require_scope::<ChatRead>(&pool)),
+        )
+        .route(
+            "/chat/room_users",
+            routing::get(manage::list_room_users).route_layer(require_scope::<ChatRead>(&pool)),
+        )
+        .route_layer(middleware::from_extractor_with_state::<Claims, _>(
+            pool.clone(),
+        ))
         .with_state(state.clone())
 }
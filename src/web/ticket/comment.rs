@@ -0,0 +1,454 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::{Pool, Sqlite};
+use tracing::info;
+
+use super::{
+    history::{self, Action, Change},
+    model,
+};
+use crate::{
+    error::{DatabaseError, Result, TicketError, ValidationError},
+    html,
+    middleware::{jwt::Claims, role::Role},
+    validation::Validator,
+};
+
+const PAGE_DEFAULT: i64 = 50;
+const PAGE_MAX: i64 = 100;
+
+struct CommentRow {
+    id: i64,
+    ticket_id: i64,
+    parent_id: Option<i64>,
+    author: Option<String>,
+    body: String,
+    created_at: i64,
+    updated_at: i64,
+    deleted_at: Option<i64>,
+    edited: bool,
+}
+
+/// Replies point to their parent through `parent_id`, comments are listed
+/// flat in the order they were written. Deleted comments stay as empty
+/// placeholders so their replies keep their place in the thread.
+#[derive(Serialize)]
+struct Comment {
+    id: i64,
+    ticket_id: i64,
+    parent_id: Option<i64>,
+    /// Missing once the author's account is deleted.
+    author: Option<String>,
+    /// Markdown source, and the same rendered to HTML.
+    body: Option<String>,
+    body_html: Option<String>,
+    created_at: i64,
+    updated_at: i64,
+    edited: bool,
+    deleted: bool,
+}
+
+impl From<CommentRow> for Comment {
+    fn from(row: CommentRow) -> Self {
+        let deleted = row.deleted_at.is_some();
+        let body = Some(row.body).filter(|_| !deleted);
+
+        Self {
+            id: row.id,
+            ticket_id: row.ticket_id,
+            parent_id: row.parent_id,
+            author: row.author,
+            body_html: body.as_deref().map(html::markdown),
+            body,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+            edited: row.edited,
+            deleted,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ListQuery {
+    /// `next_cursor` of the previous page.
+    cursor: Option<i64>,
+    limit: Option<i64>,
+}
+
+pub async fn list(
+    State(pool): State<Pool<Sqlite>>,
+    Path(id): Path<i64>,
+    Query(query): Query<ListQuery>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle get /ticket/{{id}}/comments", "Handler");
+
+    model::find(&pool, id).await?;
+
+    let limit = query.limit.unwrap_or(PAGE_DEFAULT).clamp(1, PAGE_MAX);
+    let fetch = limit + 1;
+    let cursor = query.cursor.unwrap_or(0);
+
+    // Placeholders of deleted comments are listed but not counted, as in the
+    // ticket's `comment_count`.
+    let total = sqlx::query_scalar!(
+        r#"
+        select count(*)
+        from ticket_comments
+        where ticket_id = ?
+        and deleted_at is null
+        "#,
+        id,
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?;
+
+    let mut comments = sqlx::query_as!(
+        CommentRow,
+        r#"
+        select
+            id as "id!",
+            ticket_id,
+            parent_id,
+            author,
+            body,
+            created_at,
+            updated_at,
+            deleted_at,
+            exists (
+                select 1
+                from ticket_comment_edits
+                where comment_id = ticket_comments.id
+            ) as "edited!: bool"
+        from ticket_comments
+        where ticket_id = ?
+        and id > ?
+        order by id
+        limit ?
+        "#,
+        id,
+        cursor,
+        fetch,
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?
+    .into_iter()
+    .map(Comment::from)
+    .collect::<Vec<_>>();
+
+    let next_cursor = match comments.len() as i64 > limit {
+        true => {
+            comments.truncate(limit as usize);
+            comments.last().map(|comment| comment.id)
+        }
+        false => None,
+    };
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "items": comments, "next_cursor": next_cursor, "total": total })),
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct CreatePayload {
+    body: String,
+    /// Comment this one replies to.
+    parent_id: Option<i64>,
+}
+
+pub async fn create(
+    claims: Claims,
+    State(pool): State<Pool<Sqlite>>,
+    Path(id): Path<i64>,
+    Json(payload): Json<CreatePayload>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle post /ticket/{{id}}/comments", "Handler");
+
+    Validator::new().body("body", &payload.body).finish()?;
+
+    model::find(&pool, id).await?;
+
+    if let Some(parent_id) = payload.parent_id {
+        let parent = find(&pool, id, parent_id).await;
+
+        if !parent.is_ok_and(|parent| parent.deleted_at.is_none()) {
+            let mut errors = ValidationError::default();
+            errors.add("parent_id", "is not a comment on this ticket");
+
+            return Err(errors.into());
+        }
+    }
+
+    let now = Utc::now().timestamp();
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_| DatabaseError::InsertFailed)?;
+
+    let comment_id = sqlx::query!(
+        r#"
+        insert into ticket_comments (ticket_id, parent_id, author, body, created_at, updated_at)
+        values (?, ?, ?, ?, ?, ?)
+        "#,
+        id,
+        payload.parent_id,
+        claims.sub,
+        payload.body,
+        now,
+        now,
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|_| DatabaseError::InsertFailed)?
+    .last_insert_rowid();
+
+    let change = Change::new(id, Action::Commented)
+        .field("comment")
+        .comment(comment_id)
+        .after(comment_id);
+    history::record(&mut *tx, &claims, vec![change]).await?;
+
+    tx.commit().await.map_err(|_| DatabaseError::InsertFailed)?;
+
+    let comment = Comment::from(find(&pool, id, comment_id).await?);
+
+    Ok((StatusCode::CREATED, Json(comment)))
+}
+
+#[derive(Deserialize)]
+pub struct UpdatePayload {
+    body: String,
+}
+
+/// Only the author can reword a comment. The previous wording is kept in its
+/// edit history.
+pub async fn update(
+    claims: Claims,
+    State(pool): State<Pool<Sqlite>>,
+    Path((id, comment_id)): Path<(i64, i64)>,
+    Json(payload): Json<UpdatePayload>,
+) -> Result<impl IntoResponse> {
+    info!(
+        "[{:^12}] ┃ handle patch /ticket/{{id}}/comments/{{comment_id}}",
+        "Handler"
+    );
+
+    Validator::new().body("body", &payload.body).finish()?;
+
+    model::find(&pool, id).await?;
+    let current = find(&pool, id, comment_id).await?;
+
+    if current.deleted_at.is_some() {
+        return Err(TicketError::CommentNotFound(comment_id).into());
+    }
+    if current.author.as_deref() != Some(claims.sub.as_str()) {
+        return Err(TicketError::CommentForbidden(comment_id).into());
+    }
+
+    let now = Utc::now().timestamp();
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_| DatabaseError::UpdateFailed)?;
+
+    sqlx::query!(
+        r#"
+        insert into ticket_comment_edits (comment_id, body, edited_by, edited_at)
+        values (?, ?, ?, ?)
+        "#,
+        comment_id,
+        current.body,
+        claims.sub,
+        now,
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|_| DatabaseError::InsertFailed)?;
+
+    sqlx::query!(
+        r#"
+        update ticket_comments
+        set body = ?, updated_at = ?
+        where id = ?
+        "#,
+        payload.body,
+        now,
+        comment_id,
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|_| DatabaseError::UpdateFailed)?;
+
+    let change = Change::new(id, Action::CommentEdited)
+        .field("comment")
+        .comment(comment_id)
+        .before(&current.body)
+        .after(&payload.body);
+    history::record(&mut *tx, &claims, vec![change]).await?;
+
+    tx.commit().await.map_err(|_| DatabaseError::UpdateFailed)?;
+
+    let comment = Comment::from(find(&pool, id, comment_id).await?);
+
+    Ok((StatusCode::OK, Json(comment)))
+}
+
+/// Authors can delete their own comments, admins any. The text and its edit
+/// history are removed, the comment itself stays as a placeholder.
+pub async fn delete(
+    claims: Claims,
+    State(pool): State<Pool<Sqlite>>,
+    Path((id, comment_id)): Path<(i64, i64)>,
+) -> Result<impl IntoResponse> {
+    info!(
+        "[{:^12}] ┃ handle delete /ticket/{{id}}/comments/{{comment_id}}",
+        "Handler"
+    );
+
+    model::find(&pool, id).await?;
+    let current = find(&pool, id, comment_id).await?;
+
+    if current.deleted_at.is_some() {
+        return Err(TicketError::CommentNotFound(comment_id).into());
+    }
+    if current.author.as_deref() != Some(claims.sub.as_str()) && claims.role < Role::Admin {
+        return Err(TicketError::CommentForbidden(comment_id).into());
+    }
+
+    let now = Utc::now().timestamp();
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_| DatabaseError::DeleteFailed)?;
+
+    sqlx::query!(
+        r#"
+        update ticket_comments
+        set body = '', deleted_at = ?
+        where id = ?
+        "#,
+        now,
+        comment_id,
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|_| DatabaseError::UpdateFailed)?;
+
+    // The ticket's history holds the wordings too.
+    sqlx::query!(
+        r#"
+        update ticket_history
+        set old_value = null, new_value = null
+        where comment_id = ?
+        and action = 'comment_edited'
+        "#,
+        comment_id,
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|_| DatabaseError::UpdateFailed)?;
+
+    sqlx::query!(
+        r#"
+        delete from ticket_comment_edits
+        where comment_id = ?
+        "#,
+        comment_id,
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|_| DatabaseError::DeleteFailed)?;
+
+    let change = Change::new(id, Action::CommentDeleted)
+        .field("comment")
+        .comment(comment_id)
+        .before(comment_id);
+    history::record(&mut *tx, &claims, vec![change]).await?;
+
+    tx.commit().await.map_err(|_| DatabaseError::DeleteFailed)?;
+
+    let comment = Comment::from(find(&pool, id, comment_id).await?);
+
+    Ok((StatusCode::OK, Json(comment)))
+}
+
+#[derive(Serialize)]
+struct Edit {
+    /// Wording before the edit.
+    body: String,
+    edited_by: Option<String>,
+    edited_at: i64,
+}
+
+/// Earlier versions of a comment, newest first.
+pub async fn edits(
+    State(pool): State<Pool<Sqlite>>,
+    Path((id, comment_id)): Path<(i64, i64)>,
+) -> Result<impl IntoResponse> {
+    info!(
+        "[{:^12}] ┃ handle get /ticket/{{id}}/comments/{{comment_id}}/edits",
+        "Handler"
+    );
+
+    model::find(&pool, id).await?;
+    find(&pool, id, comment_id).await?;
+
+    let edits = sqlx::query_as!(
+        Edit,
+        r#"
+        select body, edited_by, edited_at
+        from ticket_comment_edits
+        where comment_id = ?
+        order by id desc
+        "#,
+        comment_id,
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?;
+
+    Ok((StatusCode::OK, Json(edits)))
+}
+
+async fn find(pool: &Pool<Sqlite>, ticket_id: i64, comment_id: i64) -> Result<CommentRow> {
+    sqlx::query_as!(
+        CommentRow,
+        r#"
+        select
+            id as "id!",
+            ticket_id,
+            parent_id,
+            author,
+            body,
+            created_at,
+            updated_at,
+            deleted_at,
+            exists (
+                select 1
+                from ticket_comment_edits
+                where comment_id = ticket_comments.id
+            ) as "edited!: bool"
+        from ticket_comments
+        where id = ?
+        and ticket_id = ?
+        "#,
+        comment_id,
+        ticket_id,
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?
+    .ok_or(TicketError::CommentNotFound(comment_id).into())
+}
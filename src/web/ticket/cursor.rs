@@ -0,0 +1,22 @@
+use std::fmt::Display;
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+
+/// Joins the position after the last ticket of a page into a cursor. Clients
+/// get it base64 encoded and shouldn't rely on its content.
+pub fn encode(parts: &[&dyn Display]) -> String {
+    let raw = parts
+        .iter()
+        .map(|part| part.to_string())
+        .collect::<Vec<_>>()
+        .join(":");
+
+    URL_SAFE_NO_PAD.encode(raw)
+}
+
+/// The parts `cursor` was encoded from, if it is a cursor at all.
+pub fn decode(cursor: &str) -> Option<Vec<String>> {
+    let raw = String::from_utf8(URL_SAFE_NO_PAD.decode(cursor).ok()?).ok()?;
+
+    Some(raw.split(':').map(str::to_string).collect())
+}
@@ -0,0 +1,285 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sqlx::{Executor, Pool, QueryBuilder, Sqlite};
+use tracing::info;
+
+use super::model;
+use crate::{
+    error::{DatabaseError, Result},
+    middleware::{jwt::Claims, role::Role},
+};
+
+const PAGE_DEFAULT: i64 = 50;
+const PAGE_MAX: i64 = 200;
+
+/// Kinds of ticket changes, stored by name so old entries stay readable when
+/// variants are added.
+#[derive(Serialize, Deserialize, sqlx::Type, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum Action {
+    Created,
+    Updated,
+    Deleted,
+    Restored,
+    Labeled,
+    Unlabeled,
+    Commented,
+    CommentEdited,
+    CommentDeleted,
+}
+
+/// One change to a ticket, e.g.
+/// `Change::new(id, Action::Updated).field("status").before(old).after(new)`.
+pub struct Change {
+    ticket_id: i64,
+    action: Action,
+    field: Option<&'static str>,
+    comment_id: Option<i64>,
+    before: Option<Value>,
+    after: Option<Value>,
+}
+
+impl Change {
+    pub fn new(ticket_id: i64, action: Action) -> Self {
+        Self {
+            ticket_id,
+            action,
+            field: None,
+            comment_id: None,
+            before: None,
+            after: None,
+        }
+    }
+
+    /// The field of the ticket that changed. `labels` and `comment` for
+    /// label and comment changes.
+    pub fn field(mut self, field: &'static str) -> Self {
+        self.field = Some(field);
+        self
+    }
+
+    /// The comment a comment change is about.
+    pub fn comment(mut self, comment_id: i64) -> Self {
+        self.comment_id = Some(comment_id);
+        self
+    }
+
+    pub fn before(mut self, value: impl Serialize) -> Self {
+        self.before = serde_json::to_value(value).ok();
+        self
+    }
+
+    pub fn after(mut self, value: impl Serialize) -> Self {
+        self.after = serde_json::to_value(value).ok();
+        self
+    }
+
+    /// An update of `field`, unless the value stayed the same.
+    pub fn diff<T>(ticket_id: i64, field: &'static str, before: &T, after: &T) -> Option<Self>
+    where
+        T: Serialize + PartialEq,
+    {
+        (before != after).then(|| {
+            Self::new(ticket_id, Action::Updated)
+                .field(field)
+                .before(before)
+                .after(after)
+        })
+    }
+}
+
+/// Writes `changes` as made by the user of `claims`, and the admin behind
+/// it when impersonating. Takes a transaction where the changes themselves
+/// are made in one.
+pub async fn record<'e, E>(executor: E, claims: &Claims, changes: Vec<Change>) -> Result<()>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let now = Utc::now().timestamp();
+
+    let mut builder = QueryBuilder::<Sqlite>::new(
+        "insert into ticket_history \
+         (ticket_id, action, field, comment_id, old_value, new_value, actor, impersonator, \
+         created_at) ",
+    );
+
+    builder.push_values(changes, |mut row, change| {
+        row.push_bind(change.ticket_id)
+            .push_bind(change.action)
+            .push_bind(change.field)
+            .push_bind(change.comment_id)
+            .push_bind(change.before.map(|value| value.to_string()))
+            .push_bind(change.after.map(|value| value.to_string()))
+            .push_bind(claims.sub.clone())
+            .push_bind(claims.actor().map(str::to_string))
+            .push_bind(now);
+    });
+
+    builder
+        .build()
+        .execute(executor)
+        .await
+        .map_err(|_| DatabaseError::InsertFailed)?;
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct Entry {
+    id: i64,
+    ticket_id: i64,
+    action: Action,
+    field: Option<String>,
+    comment_id: Option<i64>,
+    old_value: Option<Value>,
+    new_value: Option<Value>,
+    /// Missing once the account is deleted.
+    actor: Option<String>,
+    impersonator: Option<String>,
+    created_at: i64,
+}
+
+#[derive(Deserialize)]
+pub struct HistoryQuery {
+    /// `next_cursor` of the previous page.
+    cursor: Option<i64>,
+    limit: Option<i64>,
+}
+
+/// Changes to one ticket, newest first.
+pub async fn history(
+    State(pool): State<Pool<Sqlite>>,
+    Path(id): Path<i64>,
+    Query(query): Query<HistoryQuery>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle get /ticket/{{id}}/history", "Handler");
+
+    model::find(&pool, id).await?;
+
+    let (entries, next_cursor) =
+        page(&pool, Some(id), None, false, query.cursor, query.limit).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "items": entries, "next_cursor": next_cursor })),
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct ActivityQuery {
+    actor: Option<String>,
+    /// `next_cursor` of the previous page.
+    cursor: Option<i64>,
+    limit: Option<i64>,
+}
+
+/// Changes to all tickets, newest first. Those of tickets in the trash are
+/// only shown to admins, like the trash itself.
+pub async fn activity(
+    claims: Claims,
+    State(pool): State<Pool<Sqlite>>,
+    Query(query): Query<ActivityQuery>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle get /activity", "Handler");
+
+    let (entries, next_cursor) = page(
+        &pool,
+        None,
+        query.actor.as_deref(),
+        claims.role >= Role::Admin,
+        query.cursor,
+        query.limit,
+    )
+    .await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "items": entries, "next_cursor": next_cursor })),
+    ))
+}
+
+/// Pages are chained through the id of their last entry, so entries written
+/// meanwhile don't shift them.
+async fn page(
+    pool: &Pool<Sqlite>,
+    ticket_id: Option<i64>,
+    actor: Option<&str>,
+    trashed: bool,
+    cursor: Option<i64>,
+    limit: Option<i64>,
+) -> Result<(Vec<Entry>, Option<i64>)> {
+    let limit = limit.unwrap_or(PAGE_DEFAULT).clamp(1, PAGE_MAX);
+    let fetch = limit + 1;
+
+    let mut entries = sqlx::query!(
+        r#"
+        select
+            ticket_history.id as "id!",
+            ticket_history.ticket_id,
+            ticket_history.action as "action!: Action",
+            ticket_history.field,
+            ticket_history.comment_id,
+            ticket_history.old_value,
+            ticket_history.new_value,
+            ticket_history.actor,
+            ticket_history.impersonator,
+            ticket_history.created_at
+        from ticket_history
+        join tickets on tickets.id = ticket_history.ticket_id
+        where (?1 is null or ticket_history.ticket_id = ?1)
+        and (?2 is null or ticket_history.actor = ?2)
+        and (?3 or tickets.deleted_at is null)
+        and (?4 is null or ticket_history.id < ?4)
+        order by ticket_history.id desc
+        limit ?5
+        "#,
+        ticket_id,
+        actor,
+        trashed,
+        cursor,
+        fetch,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?
+    .into_iter()
+    .map(|entry| Entry {
+        id: entry.id,
+        ticket_id: entry.ticket_id,
+        action: entry.action,
+        field: entry.field,
+        comment_id: entry.comment_id,
+        old_value: entry
+            .old_value
+            .and_then(|value| serde_json::from_str(&value).ok()),
+        new_value: entry
+            .new_value
+            .and_then(|value| serde_json::from_str(&value).ok()),
+        actor: entry.actor,
+        impersonator: entry.impersonator,
+        created_at: entry.created_at,
+    })
+    .collect::<Vec<_>>();
+
+    let next_cursor = match entries.len() as i64 > limit {
+        true => {
+            entries.truncate(limit as usize);
+            entries.last().map(|entry| entry.id)
+        }
+        false => None,
+    };
+
+    Ok((entries, next_cursor))
+}
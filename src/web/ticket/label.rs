@@ -0,0 +1,511 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, Pool, Sqlite};
+use tracing::info;
+
+use super::{
+    history::{self, Action, Change},
+    model,
+};
+use crate::{
+    error::{DatabaseError, Error, Result, TicketError, ValidationError},
+    middleware::jwt::Claims,
+    validation::Validator,
+};
+
+#[derive(Serialize)]
+struct Label {
+    id: i64,
+    name: String,
+    color: String,
+    description: String,
+    created_at: i64,
+    /// Tickets outside the trash.
+    ticket_count: i64,
+}
+
+pub async fn list(State(pool): State<Pool<Sqlite>>) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle get /labels", "Handler");
+
+    let labels = sqlx::query_as!(
+        Label,
+        r#"
+        select
+            labels.id as "id!",
+            labels.name,
+            labels.color,
+            labels.description,
+            labels.created_at,
+            count(tickets.id) as "ticket_count!: i64"
+        from labels
+        left join ticket_labels on ticket_labels.label_id = labels.id
+        left join tickets on tickets.id = ticket_labels.ticket_id
+            and tickets.deleted_at is null
+        group by labels.id
+        order by labels.name
+        "#,
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?;
+
+    Ok((StatusCode::OK, Json(labels)))
+}
+
+#[derive(Deserialize)]
+pub struct CreatePayload {
+    name: String,
+    color: String,
+    #[serde(default)]
+    description: String,
+}
+
+pub async fn create(
+    State(pool): State<Pool<Sqlite>>,
+    Json(payload): Json<CreatePayload>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle post /labels", "Handler");
+
+    Validator::new()
+        .label_name("name", &payload.name)
+        .color("color", &payload.color)
+        .description("description", &payload.description)
+        .finish()?;
+
+    let name = payload.name.trim();
+
+    let now = Utc::now().timestamp();
+
+    let id = sqlx::query!(
+        r#"
+        insert into labels (name, color, description, created_at)
+        values (?, ?, ?, ?)
+        "#,
+        name,
+        payload.color,
+        payload.description,
+        now,
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| name_taken(e, name, DatabaseError::InsertFailed))?
+    .last_insert_rowid();
+
+    let label = find(&pool, id).await?;
+
+    Ok((StatusCode::CREATED, Json(label)))
+}
+
+#[derive(Deserialize)]
+pub struct UpdatePayload {
+    name: Option<String>,
+    color: Option<String>,
+    description: Option<String>,
+}
+
+/// Tickets refer to labels by id, so a rename shows on all of them at once.
+pub async fn update(
+    State(pool): State<Pool<Sqlite>>,
+    Path(id): Path<i64>,
+    Json(payload): Json<UpdatePayload>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle patch /labels/{{id}}", "Handler");
+
+    let mut validator = Validator::new();
+
+    if let Some(name) = &payload.name {
+        validator = validator.label_name("name", name);
+    }
+    if let Some(color) = &payload.color {
+        validator = validator.color("color", color);
+    }
+    if let Some(description) = &payload.description {
+        validator = validator.description("description", description);
+    }
+
+    validator.finish()?;
+
+    let current = find(&pool, id).await?;
+
+    let name = payload
+        .name
+        .map(|name| name.trim().to_string())
+        .unwrap_or(current.name);
+    let color = payload.color.unwrap_or(current.color);
+    let description = payload.description.unwrap_or(current.description);
+
+    sqlx::query!(
+        r#"
+        update labels
+        set name = ?, color = ?, description = ?
+        where id = ?
+        "#,
+        name,
+        color,
+        description,
+        id,
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| name_taken(e, &name, DatabaseError::UpdateFailed))?;
+
+    let label = find(&pool, id).await?;
+
+    Ok((StatusCode::OK, Json(label)))
+}
+
+#[derive(Deserialize)]
+pub struct MergePayload {
+    /// Label that takes over the tickets.
+    into: i64,
+}
+
+/// Moves the tickets of a label to another one and deletes it, in a single
+/// transaction so no ticket is seen without either.
+pub async fn merge(
+    claims: Claims,
+    State(pool): State<Pool<Sqlite>>,
+    Path(id): Path<i64>,
+    Json(payload): Json<MergePayload>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle post /labels/{{id}}/merge", "Handler");
+
+    if payload.into == id {
+        let mut errors = ValidationError::default();
+        errors.add("into", "must be another label");
+
+        return Err(errors.into());
+    }
+
+    let from = find(&pool, id).await?;
+    let into = find(&pool, payload.into).await?;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_| DatabaseError::UpdateFailed)?;
+
+    let tickets = sqlx::query!(
+        r#"
+        select
+            ticket_id,
+            exists (
+                select 1
+                from ticket_labels as target
+                where target.ticket_id = ticket_labels.ticket_id
+                and target.label_id = ?
+            ) as "labeled!: bool"
+        from ticket_labels
+        where label_id = ?
+        "#,
+        payload.into,
+        id,
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?;
+
+    for ticket in tickets {
+        let mut changes = vec![
+            Change::new(ticket.ticket_id, Action::Unlabeled)
+                .field("labels")
+                .before(&from.name),
+        ];
+
+        if !ticket.labeled {
+            changes.push(
+                Change::new(ticket.ticket_id, Action::Labeled)
+                    .field("labels")
+                    .after(&into.name),
+            );
+        }
+
+        history::record(&mut *tx, &claims, changes).await?;
+    }
+
+    sqlx::query!(
+        r#"
+        insert or ignore into ticket_labels (ticket_id, label_id)
+        select ticket_id, ?
+        from ticket_labels
+        where label_id = ?
+        "#,
+        payload.into,
+        id,
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|_| DatabaseError::InsertFailed)?;
+
+    sqlx::query!(
+        r#"
+        delete from labels
+        where id = ?
+        "#,
+        id,
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|_| DatabaseError::DeleteFailed)?;
+
+    tx.commit().await.map_err(|_| DatabaseError::UpdateFailed)?;
+
+    let label = find(&pool, payload.into).await?;
+
+    Ok((StatusCode::OK, Json(label)))
+}
+
+/// Removes the label from its tickets along with it.
+pub async fn delete(
+    claims: Claims,
+    State(pool): State<Pool<Sqlite>>,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle delete /labels/{{id}}", "Handler");
+
+    let label = find(&pool, id).await?;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_| DatabaseError::DeleteFailed)?;
+
+    let tickets = sqlx::query_scalar!(
+        r#"
+        select ticket_id
+        from ticket_labels
+        where label_id = ?
+        "#,
+        id,
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?;
+
+    for ticket_id in tickets {
+        let change = Change::new(ticket_id, Action::Unlabeled)
+            .field("labels")
+            .before(&label.name);
+
+        history::record(&mut *tx, &claims, vec![change]).await?;
+    }
+
+    sqlx::query!(
+        r#"
+        delete from labels
+        where id = ?
+        "#,
+        id,
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|_| DatabaseError::DeleteFailed)?;
+
+    tx.commit().await.map_err(|_| DatabaseError::DeleteFailed)?;
+
+    Ok((StatusCode::OK, Json(label)))
+}
+
+#[derive(Deserialize)]
+pub struct LabelsPayload {
+    /// Label names.
+    labels: Vec<String>,
+}
+
+pub async fn add(
+    claims: Claims,
+    State(pool): State<Pool<Sqlite>>,
+    Path(id): Path<i64>,
+    Json(payload): Json<LabelsPayload>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle post /ticket/{{id}}/labels", "Handler");
+
+    model::find(&pool, id).await?.check_owner(&claims)?;
+
+    let labels = resolve(&pool, &payload.labels).await?;
+    let mut changes = Vec::new();
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_| DatabaseError::InsertFailed)?;
+
+    for (label_id, name) in labels {
+        let added = sqlx::query!(
+            r#"
+            insert or ignore into ticket_labels (ticket_id, label_id)
+            values (?, ?)
+            "#,
+            id,
+            label_id,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|_| DatabaseError::InsertFailed)?
+        .rows_affected();
+
+        if added > 0 {
+            changes.push(Change::new(id, Action::Labeled).field("labels").after(name));
+        }
+    }
+
+    touch(&mut *tx, id).await?;
+    history::record(&mut *tx, &claims, changes).await?;
+
+    tx.commit().await.map_err(|_| DatabaseError::InsertFailed)?;
+
+    let ticket = model::find(&pool, id).await?;
+
+    Ok((StatusCode::OK, Json(ticket)))
+}
+
+pub async fn remove(
+    claims: Claims,
+    State(pool): State<Pool<Sqlite>>,
+    Path(id): Path<i64>,
+    Json(payload): Json<LabelsPayload>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle delete /ticket/{{id}}/labels", "Handler");
+
+    model::find(&pool, id).await?.check_owner(&claims)?;
+
+    let labels = resolve(&pool, &payload.labels).await?;
+    let mut changes = Vec::new();
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_| DatabaseError::DeleteFailed)?;
+
+    for (label_id, name) in labels {
+        let removed = sqlx::query!(
+            r#"
+            delete from ticket_labels
+            where ticket_id = ?
+            and label_id = ?
+            "#,
+            id,
+            label_id,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|_| DatabaseError::DeleteFailed)?
+        .rows_affected();
+
+        if removed > 0 {
+            changes.push(
+                Change::new(id, Action::Unlabeled)
+                    .field("labels")
+                    .before(name),
+            );
+        }
+    }
+
+    touch(&mut *tx, id).await?;
+    history::record(&mut *tx, &claims, changes).await?;
+
+    tx.commit().await.map_err(|_| DatabaseError::DeleteFailed)?;
+
+    let ticket = model::find(&pool, id).await?;
+
+    Ok((StatusCode::OK, Json(ticket)))
+}
+
+/// Ids and catalogue spelling of the labels called `names`, failing on the
+/// first unknown one only after checking them all.
+async fn resolve(pool: &Pool<Sqlite>, names: &[String]) -> Result<Vec<(i64, String)>> {
+    let mut labels = Vec::with_capacity(names.len());
+    let mut errors = ValidationError::default();
+
+    for name in names {
+        let label = sqlx::query!(
+            r#"
+            select id as "id!", name
+            from labels
+            where name = ?
+            "#,
+            name,
+        )
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| DatabaseError::SelectFailed)?;
+
+        match label {
+            Some(label) => labels.push((label.id, label.name)),
+            None => errors.add("labels", format!("unknown label {name:?}")),
+        }
+    }
+
+    match errors.is_empty() {
+        true => Ok(labels),
+        false => Err(errors.into()),
+    }
+}
+
+async fn touch<'e, E>(executor: E, id: i64) -> Result<()>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let now = Utc::now().timestamp();
+
+    sqlx::query!(
+        r#"
+        update tickets
+        set updated_at = ?
+        where id = ?
+        "#,
+        now,
+        id,
+    )
+    .execute(executor)
+    .await
+    .map_err(|_| DatabaseError::UpdateFailed)?;
+
+    Ok(())
+}
+
+/// Names are unique regardless of case. The database enforces it, so two
+/// requests can't both take the same name.
+fn name_taken(e: sqlx::Error, name: &str, otherwise: DatabaseError) -> Error {
+    match e
+        .as_database_error()
+        .is_some_and(|e| e.is_unique_violation())
+    {
+        true => TicketError::LabelTaken(name.to_string()).into(),
+        false => otherwise.into(),
+    }
+}
+
+async fn find(pool: &Pool<Sqlite>, id: i64) -> Result<Label> {
+    sqlx::query_as!(
+        Label,
+        r#"
+        select
+            labels.id as "id!",
+            labels.name,
+            labels.color,
+            labels.description,
+            labels.created_at,
+            (
+                select count(*)
+                from ticket_labels
+                join tickets on tickets.id = ticket_labels.ticket_id
+                where label_id = labels.id
+                and tickets.deleted_at is null
+            ) as "ticket_count!: i64"
+        from labels
+        where id = ?
+        "#,
+        id,
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?
+    .ok_or(TicketError::LabelNotFound(id).into())
+}
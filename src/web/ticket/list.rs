@@ -0,0 +1,281 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::{Pool, QueryBuilder, Sqlite};
+use tracing::info;
+
+use super::{
+    cursor,
+    model::{self, Priority, Status, Ticket},
+};
+use crate::{
+    error::{DatabaseError, Result, ValidationError},
+    validation::{Validator, parse_variant},
+};
+
+const PAGE_DEFAULT: i64 = 50;
+const PAGE_MAX: i64 = 100;
+
+/// Indexed columns tickets can be sorted on. Status and priority sort by
+/// workflow and urgency rather than alphabetically.
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Sort {
+    #[default]
+    Id,
+    CreatedAt,
+    UpdatedAt,
+    Status,
+    Priority,
+}
+
+impl Sort {
+    fn expression(self) -> &'static str {
+        match self {
+            Self::Id => "id",
+            Self::CreatedAt => "created_at",
+            Self::UpdatedAt => "updated_at",
+            Self::Status => {
+                "case status when 'open' then 0 when 'in_progress' then 1 \
+                 when 'resolved' then 2 else 3 end"
+            }
+            Self::Priority => {
+                "case priority when 'low' then 0 when 'medium' then 1 \
+                 when 'high' then 2 else 3 end"
+            }
+        }
+    }
+
+    /// Value of [`Self::expression`] for `ticket`.
+    fn key(self, ticket: &Ticket) -> i64 {
+        match self {
+            Self::Id => ticket.id,
+            Self::CreatedAt => ticket.created_at,
+            Self::UpdatedAt => ticket.updated_at,
+            Self::Status => ticket.status as i64,
+            Self::Priority => ticket.priority as i64,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Id => "id",
+            Self::CreatedAt => "created_at",
+            Self::UpdatedAt => "updated_at",
+            Self::Status => "status",
+            Self::Priority => "priority",
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Order {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl Order {
+    fn keyword(self) -> &'static str {
+        match self {
+            Self::Asc => "asc",
+            Self::Desc => "desc",
+        }
+    }
+
+    fn comparison(self) -> &'static str {
+        match self {
+            Self::Asc => ">",
+            Self::Desc => "<",
+        }
+    }
+}
+
+/// Position after the last ticket of a page.
+struct Cursor {
+    sort: Sort,
+    order: Order,
+    key: i64,
+    id: i64,
+}
+
+impl Cursor {
+    fn encode(&self) -> String {
+        cursor::encode(&[
+            &self.sort.name(),
+            &self.order.keyword(),
+            &self.key,
+            &self.id,
+        ])
+    }
+
+    /// Cursors only continue the listing they were issued for.
+    fn decode(cursor: &str, sort: Sort, order: Order) -> Option<Self> {
+        match cursor::decode(cursor)?.as_slice() {
+            [name, keyword, key, id] if name == sort.name() && keyword == order.keyword() => {
+                Some(Self {
+                    sort,
+                    order,
+                    key: key.parse().ok()?,
+                    id: id.parse().ok()?,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ListQuery {
+    status: Option<String>,
+    priority: Option<String>,
+    assignee: Option<String>,
+    reporter: Option<String>,
+    /// Comma separated label names, tickets need to have all of them.
+    label: Option<String>,
+    /// Unix timestamps, `*_after` inclusive and `*_before` exclusive.
+    created_after: Option<i64>,
+    created_before: Option<i64>,
+    updated_after: Option<i64>,
+    updated_before: Option<i64>,
+    #[serde(default)]
+    sort: Sort,
+    #[serde(default)]
+    order: Order,
+    cursor: Option<String>,
+    limit: Option<i64>,
+}
+
+/// Tickets matching the filters, one page at a time. `total` counts all
+/// matches, not only those on the page. Tickets in the trash are left out.
+pub async fn list(
+    State(pool): State<Pool<Sqlite>>,
+    Query(query): Query<ListQuery>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle get /ticket", "Handler");
+
+    Validator::new()
+        .variant::<Status>("status", query.status.as_deref())
+        .variant::<Priority>("priority", query.priority.as_deref())
+        .finish()?;
+
+    let cursor = match &query.cursor {
+        Some(cursor) => Some(
+            Cursor::decode(cursor, query.sort, query.order).ok_or_else(|| {
+                let mut errors = ValidationError::default();
+                errors.add("cursor", "is invalid or belongs to another sort order");
+                errors
+            })?,
+        ),
+        None => None,
+    };
+
+    let limit = query.limit.unwrap_or(PAGE_DEFAULT).clamp(1, PAGE_MAX);
+
+    let mut count = QueryBuilder::<Sqlite>::new("select count(*) from tickets");
+    push_filters(&mut count, &query);
+
+    let total = count
+        .build_query_scalar::<i64>()
+        .fetch_one(&pool)
+        .await
+        .map_err(|_| DatabaseError::SelectFailed)?;
+
+    let expression = query.sort.expression();
+    let comparison = query.order.comparison();
+    let keyword = query.order.keyword();
+
+    let mut page = QueryBuilder::<Sqlite>::new(model::SELECT);
+    push_filters(&mut page, &query);
+
+    if let Some(cursor) = &cursor {
+        page.push(format!(" and (({expression}) {comparison} "))
+            .push_bind(cursor.key)
+            .push(format!(" or (({expression}) = "))
+            .push_bind(cursor.key)
+            .push(format!(" and id {comparison} "))
+            .push_bind(cursor.id)
+            .push("))");
+    }
+
+    page.push(format!(
+        " order by ({expression}) {keyword}, id {keyword} limit "
+    ))
+    .push_bind(limit + 1);
+
+    let mut items = page
+        .build_query_as::<Ticket>()
+        .fetch_all(&pool)
+        .await
+        .map_err(|_| DatabaseError::SelectFailed)?;
+
+    let next_cursor = match items.len() as i64 > limit {
+        true => {
+            items.truncate(limit as usize);
+            items.last().map(|ticket| {
+                Cursor {
+                    sort: query.sort,
+                    order: query.order,
+                    key: query.sort.key(ticket),
+                    id: ticket.id,
+                }
+                .encode()
+            })
+        }
+        false => None,
+    };
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "items": items, "next_cursor": next_cursor, "total": total })),
+    ))
+}
+
+fn push_filters(builder: &mut QueryBuilder<Sqlite>, query: &ListQuery) {
+    builder.push(" where deleted_at is null");
+
+    if let Some(status) = query.status.as_deref().and_then(parse_variant::<Status>) {
+        builder.push(" and status = ").push_bind(status);
+    }
+    if let Some(priority) = query
+        .priority
+        .as_deref()
+        .and_then(parse_variant::<Priority>)
+    {
+        builder.push(" and priority = ").push_bind(priority);
+    }
+    if let Some(assignee) = &query.assignee {
+        builder.push(" and assignee = ").push_bind(assignee.clone());
+    }
+    if let Some(reporter) = &query.reporter {
+        builder.push(" and reporter = ").push_bind(reporter.clone());
+    }
+    for label in query.label.iter().flat_map(|labels| labels.split(',')) {
+        builder
+            .push(
+                " and exists (select 1 from ticket_labels \
+                 join labels on labels.id = ticket_labels.label_id \
+                 where ticket_labels.ticket_id = tickets.id and labels.name = ",
+            )
+            .push_bind(label.trim().to_string())
+            .push(")");
+    }
+    if let Some(created_after) = query.created_after {
+        builder.push(" and created_at >= ").push_bind(created_after);
+    }
+    if let Some(created_before) = query.created_before {
+        builder.push(" and created_at < ").push_bind(created_before);
+    }
+    if let Some(updated_after) = query.updated_after {
+        builder.push(" and updated_at >= ").push_bind(updated_after);
+    }
+    if let Some(updated_before) = query.updated_before {
+        builder.push(" and updated_at < ").push_bind(updated_before);
+    }
+}
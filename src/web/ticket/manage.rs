@@ -0,0 +1,346 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::{Pool, Sqlite};
+use tracing::info;
+
+use super::{
+    history::{self, Action, Change},
+    model::{self, Priority, Status},
+};
+use crate::{
+    audit::{Entry, Event},
+    error::{DatabaseError, Result, TicketError},
+    middleware::{client::ClientInfo, jwt::Claims},
+    validation::{Validator, parse_variant},
+    web::nullable,
+};
+
+#[derive(Deserialize)]
+pub struct CreatePayload {
+    title: String,
+    #[serde(default)]
+    description: String,
+    priority: Option<String>,
+    assignee: Option<String>,
+}
+
+pub async fn create(
+    claims: Claims,
+    State(pool): State<Pool<Sqlite>>,
+    Json(payload): Json<CreatePayload>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle post /ticket", "Handler");
+
+    Validator::new()
+        .title("title", &payload.title)
+        .description("description", &payload.description)
+        .variant::<Priority>("priority", payload.priority.as_deref())
+        .finish()?;
+
+    model::check_assignee(&pool, payload.assignee.as_deref()).await?;
+
+    let title = payload.title.trim();
+    let priority = payload
+        .priority
+        .as_deref()
+        .and_then(parse_variant)
+        .unwrap_or(Priority::Medium);
+    let now = Utc::now().timestamp();
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_| DatabaseError::InsertFailed)?;
+
+    let id = sqlx::query!(
+        r#"
+        insert into tickets (
+            title, description, status, priority, reporter, assignee, created_at, updated_at
+        )
+        values (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+        title,
+        payload.description,
+        Status::Open,
+        priority,
+        claims.sub,
+        payload.assignee,
+        now,
+        now,
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|_| DatabaseError::InsertFailed)?
+    .last_insert_rowid();
+
+    let change = Change::new(id, Action::Created).after(json!({
+        "title": title,
+        "description": payload.description,
+        "status": Status::Open,
+        "priority": priority,
+        "assignee": payload.assignee,
+    }));
+    history::record(&mut *tx, &claims, vec![change]).await?;
+
+    tx.commit().await.map_err(|_| DatabaseError::InsertFailed)?;
+
+    let ticket = model::find(&pool, id).await?;
+
+    Ok((StatusCode::CREATED, Json(ticket)))
+}
+
+pub async fn get(
+    State(pool): State<Pool<Sqlite>>,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle get /ticket/{{id}}", "Handler");
+
+    let ticket = model::find(&pool, id).await?;
+
+    Ok((StatusCode::OK, Json(ticket)))
+}
+
+/// Fields that are left out stay as they are, a `null` assignee unassigns.
+#[derive(Deserialize)]
+pub struct UpdatePayload {
+    title: Option<String>,
+    description: Option<String>,
+    status: Option<String>,
+    priority: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    assignee: Option<Option<String>>,
+}
+
+pub async fn update(
+    claims: Claims,
+    State(pool): State<Pool<Sqlite>>,
+    Path(id): Path<i64>,
+    Json(payload): Json<UpdatePayload>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle patch /ticket/{{id}}", "Handler");
+
+    let mut validator = Validator::new()
+        .variant::<Status>("status", payload.status.as_deref())
+        .variant::<Priority>("priority", payload.priority.as_deref());
+
+    if let Some(title) = &payload.title {
+        validator = validator.title("title", title);
+    }
+    if let Some(description) = &payload.description {
+        validator = validator.description("description", description);
+    }
+
+    validator.finish()?;
+
+    let now = Utc::now().timestamp();
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_| DatabaseError::UpdateFailed)?;
+
+    // Writing first takes the database's write lock, so the ticket can't
+    // change between reading it here and writing it back below.
+    let found = sqlx::query!(
+        r#"
+        update tickets
+        set updated_at = ?
+        where id = ?
+        and deleted_at is null
+        "#,
+        now,
+        id,
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|_| DatabaseError::UpdateFailed)?
+    .rows_affected();
+
+    if found == 0 {
+        return Err(TicketError::NotFound(id).into());
+    }
+
+    let current = model::find(&mut *tx, id).await?;
+    current.check_owner(&claims)?;
+
+    if let Some(assignee) = &payload.assignee {
+        model::check_assignee(&pool, assignee.as_deref()).await?;
+    }
+
+    let title = payload
+        .title
+        .map(|title| title.trim().to_string())
+        .unwrap_or_else(|| current.title.clone());
+    let description = payload
+        .description
+        .unwrap_or_else(|| current.description.clone());
+    let status = payload
+        .status
+        .as_deref()
+        .and_then(parse_variant)
+        .unwrap_or(current.status);
+    let priority = payload
+        .priority
+        .as_deref()
+        .and_then(parse_variant)
+        .unwrap_or(current.priority);
+    let assignee = payload.assignee.unwrap_or_else(|| current.assignee.clone());
+
+    let changes = [
+        Change::diff(id, "title", &current.title, &title),
+        Change::diff(id, "description", &current.description, &description),
+        Change::diff(id, "status", &current.status, &status),
+        Change::diff(id, "priority", &current.priority, &priority),
+        Change::diff(id, "assignee", &current.assignee, &assignee),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+
+    sqlx::query!(
+        r#"
+        update tickets
+        set title = ?, description = ?, status = ?, priority = ?, assignee = ?, updated_at = ?
+        where id = ?
+        "#,
+        title,
+        description,
+        status,
+        priority,
+        assignee,
+        now,
+        id,
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|_| DatabaseError::UpdateFailed)?;
+
+    history::record(&mut *tx, &claims, changes).await?;
+
+    tx.commit().await.map_err(|_| DatabaseError::UpdateFailed)?;
+
+    let ticket = model::find(&pool, id).await?;
+
+    Ok((StatusCode::OK, Json(ticket)))
+}
+
+#[derive(Deserialize)]
+pub struct IdPayload {
+    id: i64,
+}
+
+/// Moves the ticket to the trash, where it can be restored until it is
+/// purged.
+pub async fn delete(
+    claims: Claims,
+    client: ClientInfo,
+    State(pool): State<Pool<Sqlite>>,
+    Query(payload): Query<IdPayload>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle delete /ticket", "Handler");
+
+    let ticket = model::find(&pool, payload.id).await?;
+    ticket.check_owner(&claims)?;
+
+    let now = Utc::now().timestamp();
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_| DatabaseError::DeleteFailed)?;
+
+    let deleted = sqlx::query!(
+        r#"
+        update tickets
+        set deleted_at = ?, deleted_by = ?
+        where id = ?
+        and deleted_at is null
+        "#,
+        now,
+        claims.sub,
+        payload.id,
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|_| DatabaseError::DeleteFailed)?
+    .rows_affected();
+
+    // Deleted by someone else meanwhile.
+    if deleted == 0 {
+        return Err(TicketError::NotFound(payload.id).into());
+    }
+
+    let change = Change::new(ticket.id, Action::Deleted);
+    history::record(&mut *tx, &claims, vec![change]).await?;
+
+    tx.commit().await.map_err(|_| DatabaseError::DeleteFailed)?;
+
+    Entry::new(Event::TicketDeleted, &client)
+        .by(&claims)
+        .target(ticket.id)
+        .record(&pool)
+        .await;
+
+    let ticket = model::find_deleted(&pool, payload.id).await?;
+
+    Ok((StatusCode::OK, Json(ticket)))
+}
+
+/// Takes the ticket back out of the trash. Allowed to those who could delete
+/// it.
+pub async fn restore(
+    claims: Claims,
+    client: ClientInfo,
+    State(pool): State<Pool<Sqlite>>,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle post /ticket/{{id}}/restore", "Handler");
+
+    let ticket = model::find_deleted(&pool, id).await?;
+    ticket.check_owner(&claims)?;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_| DatabaseError::UpdateFailed)?;
+
+    let restored = sqlx::query!(
+        r#"
+        update tickets
+        set deleted_at = null, deleted_by = null
+        where id = ?
+        and deleted_at is not null
+        "#,
+        id,
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|_| DatabaseError::UpdateFailed)?
+    .rows_affected();
+
+    if restored == 0 {
+        return Err(TicketError::NotFound(id).into());
+    }
+
+    let change = Change::new(id, Action::Restored);
+    history::record(&mut *tx, &claims, vec![change]).await?;
+
+    tx.commit().await.map_err(|_| DatabaseError::UpdateFailed)?;
+
+    Entry::new(Event::TicketRestored, &client)
+        .by(&claims)
+        .target(id)
+        .record(&pool)
+        .await;
+
+    let ticket = model::find(&pool, id).await?;
+
+    Ok((StatusCode::OK, Json(ticket)))
+}
@@ -0,0 +1,129 @@
+mod comment;
+mod cursor;
+mod history;
+mod label;
+mod list;
+mod manage;
+mod model;
+mod search;
+mod trash;
+
+pub use trash::purge;
+
+use axum::{Router, middleware, routing};
+use sqlx::{Pool, Sqlite};
+
+use crate::middleware::{
+    jwt::Claims,
+    role::{Admin, Member, require_role},
+    scope::{TicketsRead, TicketsWrite, require_scope},
+};
+
+pub fn router(pool: Pool<Sqlite>) -> Router {
+    Router::new()
+        .route(
+            "/ticket",
+            routing::post(manage::create)
+                .route_layer(require_role::<Member>(&pool))
+                .route_layer(require_scope::<TicketsWrite>(&pool)),
+        )
+        .route(
+            "/ticket",
+            routing::get(list::list).route_layer(require_scope::<TicketsRead>(&pool)),
+        )
+        .route(
+            "/ticket",
+            routing::delete(manage::delete)
+                .route_layer(require_role::<Member>(&pool))
+                .route_layer(require_scope::<TicketsWrite>(&pool)),
+        )
+        .route(
+            "/ticket/search",
+            routing::get(search::search).route_layer(require_scope::<TicketsRead>(&pool)),
+        )
+        .route(
+            "/ticket/trash",
+            routing::get(trash::list)
+                .route_layer(require_role::<Admin>(&pool))
+                .route_layer(require_scope::<TicketsRead>(&pool)),
+        )
+        .route(
+            "/ticket/{id}",
+            routing::get(manage::get).route_layer(require_scope::<TicketsRead>(&pool)),
+        )
+        .route(
+            "/ticket/{id}",
+            routing::patch(manage::update)
+                .route_layer(require_role::<Member>(&pool))
+                .route_layer(require_scope::<TicketsWrite>(&pool)),
+        )
+        .route(
+            "/ticket/{id}/restore",
+            routing::post(manage::restore)
+                .route_layer(require_role::<Member>(&pool))
+                .route_layer(require_scope::<TicketsWrite>(&pool)),
+        )
+        .route(
+            "/ticket/{id}/comments",
+            routing::get(comment::list).route_layer(require_scope::<TicketsRead>(&pool)),
+        )
+        .route(
+            "/ticket/{id}/comments",
+            routing::post(comment::create)
+                .route_layer(require_role::<Member>(&pool))
+                .route_layer(require_scope::<TicketsWrite>(&pool)),
+        )
+        .route(
+            "/ticket/{id}/comments/{comment_id}",
+            routing::patch(comment::update)
+                .delete(comment::delete)
+                .route_layer(require_role::<Member>(&pool))
+                .route_layer(require_scope::<TicketsWrite>(&pool)),
+        )
+        .route(
+            "/ticket/{id}/comments/{comment_id}/edits",
+            routing::get(comment::edits).route_layer(require_scope::<TicketsRead>(&pool)),
+        )
+        .route(
+            "/ticket/{id}/history",
+            routing::get(history::history).route_layer(require_scope::<TicketsRead>(&pool)),
+        )
+        .route(
+            "/activity",
+            routing::get(history::activity).route_layer(require_scope::<TicketsRead>(&pool)),
+        )
+        .route(
+            "/ticket/{id}/labels",
+            routing::post(label::add)
+                .delete(label::remove)
+                .route_layer(require_role::<Member>(&pool))
+                .route_layer(require_scope::<TicketsWrite>(&pool)),
+        )
+        .route(
+            "/labels",
+            routing::get(label::list).route_layer(require_scope::<TicketsRead>(&pool)),
+        )
+        .route(
+            "/labels",
+            routing::post(label::create)
+                .route_layer(require_role::<Member>(&pool))
+                .route_layer(require_scope::<TicketsWrite>(&pool)),
+        )
+        .route(
+            "/labels/{id}",
+            routing::patch(label::update)
+                .delete(label::delete)
+                .route_layer(require_role::<Admin>(&pool))
+                .route_layer(require_scope::<TicketsWrite>(&pool)),
+        )
+        .route(
+            "/labels/{id}/merge",
+            routing::post(label::merge)
+                .route_layer(require_role::<Admin>(&pool))
+                .route_layer(require_scope::<TicketsWrite>(&pool)),
+        )
+        .route_layer(middleware::from_extractor_with_state::<Claims, _>(
+            pool.clone(),
+        ))
+        .with_state(pool.clone())
+}
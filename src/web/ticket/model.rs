@@ -0,0 +1,194 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, Pool, Sqlite, types::Json};
+
+use crate::{
+    error::{DatabaseError, Result, TicketError, ValidationError},
+    middleware::{jwt::Claims, role::Role},
+};
+
+#[derive(Serialize, Deserialize, sqlx::Type, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum Status {
+    Open,
+    InProgress,
+    Resolved,
+    Closed,
+}
+
+/// Ordered by urgency, so `priority >= Priority::High` reads as "at least high".
+#[derive(
+    Serialize, Deserialize, sqlx::Type, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum Priority {
+    Low,
+    Medium,
+    High,
+    Urgent,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct Ticket {
+    pub id: i64,
+    pub title: String,
+    pub description: String,
+    pub status: Status,
+    pub priority: Priority,
+    /// Missing on tickets created before reporters were recorded, and once
+    /// the reporter's account is deleted.
+    pub reporter: Option<String>,
+    pub assignee: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    /// Comments that weren't deleted.
+    pub comment_count: i64,
+    /// Label names in alphabetical order.
+    pub labels: Json<Vec<String>>,
+    /// Set while the ticket is in the trash.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_by: Option<String>,
+}
+
+/// Selects [`Ticket`]s for a `QueryBuilder`, to be followed by filters.
+pub const SELECT: &str = r#"
+    select
+        id, title, description, status, priority, reporter, assignee,
+        created_at, updated_at,
+        (
+            select count(*)
+            from ticket_comments
+            where ticket_id = tickets.id
+            and deleted_at is null
+        ) as comment_count,
+        (
+            select json_group_array(name)
+            from (
+                select labels.name
+                from ticket_labels
+                join labels on labels.id = ticket_labels.label_id
+                where ticket_labels.ticket_id = tickets.id
+                order by labels.name
+            )
+        ) as labels,
+        deleted_at,
+        deleted_by
+    from tickets
+"#;
+
+/// Tickets in the trash are treated as gone. Takes a transaction where the
+/// ticket is about to be written back.
+pub async fn find<'e, E>(executor: E, id: i64) -> Result<Ticket>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    fetch(executor, id)
+        .await?
+        .filter(|ticket| ticket.deleted_at.is_none())
+        .ok_or(TicketError::NotFound(id).into())
+}
+
+/// Only finds tickets in the trash.
+pub async fn find_deleted<'e, E>(executor: E, id: i64) -> Result<Ticket>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    fetch(executor, id)
+        .await?
+        .filter(|ticket| ticket.deleted_at.is_some())
+        .ok_or(TicketError::NotFound(id).into())
+}
+
+async fn fetch<'e, E>(executor: E, id: i64) -> Result<Option<Ticket>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let ticket = sqlx::query_as!(
+        Ticket,
+        r#"
+        select
+            id as "id!",
+            title,
+            description,
+            status as "status: Status",
+            priority as "priority: Priority",
+            reporter,
+            assignee,
+            created_at,
+            updated_at,
+            (
+                select count(*)
+                from ticket_comments
+                where ticket_id = tickets.id
+                and deleted_at is null
+            ) as "comment_count!: i64",
+            (
+                select json_group_array(name)
+                from (
+                    select labels.name
+                    from ticket_labels
+                    join labels on labels.id = ticket_labels.label_id
+                    where ticket_labels.ticket_id = tickets.id
+                    order by labels.name
+                )
+            ) as "labels!: Json<Vec<String>>",
+            deleted_at,
+            deleted_by
+        from tickets
+        where id = ?
+        "#,
+        id,
+    )
+    .fetch_optional(executor)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?;
+
+    Ok(ticket)
+}
+
+impl Ticket {
+    /// Whether `claims` may change or delete the ticket. Anyone who can read
+    /// tickets can see it.
+    pub fn check_owner(&self, claims: &Claims) -> Result<()> {
+        let owner = [&self.reporter, &self.assignee]
+            .into_iter()
+            .any(|user| user.as_deref() == Some(claims.sub.as_str()));
+
+        match owner || claims.role >= Role::Admin {
+            true => Ok(()),
+            false => Err(TicketError::Forbidden(self.id).into()),
+        }
+    }
+}
+
+/// Assignees have to be existing users. Reported as a validation error, as
+/// it's the payload that is wrong rather than the ticket missing.
+pub async fn check_assignee(pool: &Pool<Sqlite>, assignee: Option<&str>) -> Result<()> {
+    let Some(assignee) = assignee else {
+        return Ok(());
+    };
+
+    let user = sqlx::query!(
+        r#"
+        select username
+        from users
+        where username = ?
+        "#,
+        assignee,
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?;
+
+    if user.is_none() {
+        let mut errors = ValidationError::default();
+        errors.add("assignee", "is not a known user");
+
+        return Err(errors.into());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,158 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::{Pool, Sqlite};
+use tracing::info;
+
+use super::model::{Priority, Status};
+use crate::{
+    error::{DatabaseError, Error, Result, ValidationError},
+    html,
+    validation::Validator,
+};
+
+const PAGE_DEFAULT: i64 = 20;
+const PAGE_MAX: i64 = 100;
+
+/// Words around a match shown in description snippets.
+const SNIPPET_TOKENS: i64 = 16;
+
+/// Title matches count ten times as much as description matches.
+const TITLE_WEIGHT: f64 = 10.0;
+const DESCRIPTION_WEIGHT: f64 = 1.0;
+
+// Control characters can't occur in tickets, so they safely mark matches
+// until the text is escaped.
+const MATCH_START: &str = "\u{1}";
+const MATCH_END: &str = "\u{2}";
+
+#[derive(Deserialize)]
+pub struct SearchQuery {
+    /// FTS5 query: words, `"quoted phrases"`, `prefix*`, `AND`, `OR`, `NOT`.
+    q: String,
+    limit: Option<i64>,
+    offset: Option<i64>,
+}
+
+#[derive(Serialize)]
+struct Hit {
+    id: i64,
+    title: String,
+    status: Status,
+    priority: Priority,
+    /// bm25 score, lower is better.
+    rank: f64,
+    /// HTML with matches wrapped in `<mark>`.
+    title_highlight: String,
+    description_snippet: String,
+}
+
+/// Best matches first, tickets in the trash are left out.
+pub async fn search(
+    State(pool): State<Pool<Sqlite>>,
+    Query(query): Query<SearchQuery>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle get /ticket/search", "Handler");
+
+    Validator::new()
+        .title("q", &query.q)
+        .positive("limit", query.limit)
+        .finish()?;
+
+    let limit = query.limit.unwrap_or(PAGE_DEFAULT).min(PAGE_MAX);
+    let offset = query.offset.unwrap_or(0).max(0);
+
+    let total = sqlx::query_scalar!(
+        r#"
+        select count(*)
+        from tickets_fts
+        join tickets on tickets.id = tickets_fts.rowid
+        where tickets_fts match ?
+        and tickets.deleted_at is null
+        "#,
+        query.q,
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(syntax_error)?;
+
+    let items = sqlx::query!(
+        r#"
+        select
+            tickets.id as "id!",
+            tickets.title,
+            tickets.status as "status: Status",
+            tickets.priority as "priority: Priority",
+            bm25(tickets_fts, ?, ?) as "score!: f64",
+            highlight(tickets_fts, 0, ?, ?) as "title_highlight!: String",
+            snippet(tickets_fts, 1, ?, ?, '…', ?) as "description_snippet!: String"
+        from tickets_fts
+        join tickets on tickets.id = tickets_fts.rowid
+        where tickets_fts match ?
+        and tickets.deleted_at is null
+        order by bm25(tickets_fts, ?, ?)
+        limit ? offset ?
+        "#,
+        TITLE_WEIGHT,
+        DESCRIPTION_WEIGHT,
+        MATCH_START,
+        MATCH_END,
+        MATCH_START,
+        MATCH_END,
+        SNIPPET_TOKENS,
+        query.q,
+        TITLE_WEIGHT,
+        DESCRIPTION_WEIGHT,
+        limit,
+        offset,
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(syntax_error)?
+    .into_iter()
+    .map(|hit| Hit {
+        id: hit.id,
+        title: hit.title,
+        status: hit.status,
+        priority: hit.priority,
+        rank: hit.score,
+        title_highlight: highlight(&hit.title_highlight),
+        description_snippet: highlight(&hit.description_snippet),
+    })
+    .collect::<Vec<_>>();
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "items": items, "total": total })),
+    ))
+}
+
+fn highlight(marked: &str) -> String {
+    html::escape(marked)
+        .replace(MATCH_START, "<mark>")
+        .replace(MATCH_END, "</mark>")
+}
+
+/// Malformed queries are the client's fault, everything else is ours. FTS5
+/// reports them in several wordings, but always as the generic SQLITE_ERROR,
+/// which the statements themselves can't cause.
+fn syntax_error(e: sqlx::Error) -> Error {
+    let syntax = e
+        .as_database_error()
+        .and_then(|e| e.code())
+        .is_some_and(|code| code == "1");
+
+    match syntax {
+        true => {
+            let mut errors = ValidationError::default();
+            errors.add("q", "is not a valid search query");
+            errors.into()
+        }
+        false => DatabaseError::SelectFailed.into(),
+    }
+}
@@ -0,0 +1,181 @@
+use std::time::Duration;
+
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::{Pool, QueryBuilder, Sqlite};
+use tokio::time;
+use tracing::{error, info};
+
+use super::{
+    cursor,
+    model::{self, Ticket},
+};
+use crate::{
+    audit::{Entry, Event},
+    config::config,
+    error::{DatabaseError, Result, ValidationError},
+    middleware::client::ClientInfo,
+};
+
+const PAGE_DEFAULT: i64 = 50;
+const PAGE_MAX: i64 = 100;
+
+#[derive(Serialize)]
+struct Trashed {
+    #[serde(flatten)]
+    ticket: Ticket,
+    /// When the ticket is removed for good.
+    purge_at: i64,
+}
+
+/// Position after the last ticket of a page. Holds the deletion time, so a
+/// page continues where it ended even if that ticket was restored meanwhile.
+struct Cursor {
+    deleted_at: i64,
+    id: i64,
+}
+
+impl Cursor {
+    fn encode(&self) -> String {
+        cursor::encode(&[&self.deleted_at, &self.id])
+    }
+
+    fn decode(cursor: &str) -> Option<Self> {
+        match cursor::decode(cursor)?.as_slice() {
+            [deleted_at, id] => Some(Self {
+                deleted_at: deleted_at.parse().ok()?,
+                id: id.parse().ok()?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct TrashQuery {
+    /// `next_cursor` of the previous page.
+    cursor: Option<String>,
+    limit: Option<i64>,
+}
+
+/// Deleted tickets, most recently deleted first.
+pub async fn list(
+    State(pool): State<Pool<Sqlite>>,
+    Query(query): Query<TrashQuery>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle get /ticket/trash", "Handler");
+
+    let cursor = match &query.cursor {
+        Some(cursor) => Some(Cursor::decode(cursor).ok_or_else(|| {
+            let mut errors = ValidationError::default();
+            errors.add("cursor", "is invalid");
+            errors
+        })?),
+        None => None,
+    };
+
+    let limit = query.limit.unwrap_or(PAGE_DEFAULT).clamp(1, PAGE_MAX);
+
+    let total = sqlx::query_scalar!(
+        r#"
+        select count(*)
+        from tickets
+        where deleted_at is not null
+        "#,
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?;
+
+    let mut page = QueryBuilder::<Sqlite>::new(model::SELECT);
+    page.push(" where deleted_at is not null");
+
+    if let Some(cursor) = &cursor {
+        page.push(" and (deleted_at, id) < (")
+            .push_bind(cursor.deleted_at)
+            .push(", ")
+            .push_bind(cursor.id)
+            .push(")");
+    }
+
+    page.push(" order by deleted_at desc, id desc limit ")
+        .push_bind(limit + 1);
+
+    let mut items = page
+        .build_query_as::<Ticket>()
+        .fetch_all(&pool)
+        .await
+        .map_err(|_| DatabaseError::SelectFailed)?;
+
+    let next_cursor = match items.len() as i64 > limit {
+        true => {
+            items.truncate(limit as usize);
+            items.last().map(|ticket| {
+                Cursor {
+                    deleted_at: ticket.deleted_at.unwrap_or_default(),
+                    id: ticket.id,
+                }
+                .encode()
+            })
+        }
+        false => None,
+    };
+
+    let retention = config().ticket.retention;
+    let items = items
+        .into_iter()
+        .map(|ticket| Trashed {
+            purge_at: ticket.deleted_at.unwrap_or_default() + retention,
+            ticket,
+        })
+        .collect::<Vec<_>>();
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "items": items, "next_cursor": next_cursor, "total": total })),
+    ))
+}
+
+/// Removes tickets that have been in the trash for longer than the retention
+/// period, along with their comments, labels and history. Runs until the
+/// server stops.
+pub async fn purge(pool: Pool<Sqlite>) {
+    let mut interval = time::interval(Duration::from_secs(config().ticket.purge_interval.max(1)));
+
+    loop {
+        interval.tick().await;
+
+        let cutoff = Utc::now().timestamp() - config().ticket.retention;
+
+        let purged = sqlx::query_scalar!(
+            r#"
+            delete from tickets
+            where deleted_at < ?
+            returning id
+            "#,
+            cutoff,
+        )
+        .fetch_all(&pool)
+        .await;
+
+        match purged {
+            Ok(ids) if ids.is_empty() => {}
+            Ok(ids) => {
+                info!("[{:^12}] ━ purged {} tickets", "Trash", ids.len());
+
+                Entry::new(Event::TicketsPurged, &ClientInfo::default())
+                    .details(json!({ "tickets": ids }))
+                    .record(&pool)
+                    .await;
+            }
+            Err(e) => error!("[{:^12}] ━ purge failed: {e:?}", "Trash"),
+        }
+    }
+}
@@ -0,0 +1,265 @@
+use axum::{
+    Extension, Json, Router, extract::State, http::StatusCode, response::IntoResponse, routing,
+};
+use chrono::{Duration, Utc};
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::{Pool, Sqlite};
+use tracing::{info, warn};
+
+use crate::{
+    audit::{Entry, Event},
+    config::config,
+    crypt::{keys::keys, token},
+    error::{AuthError, DatabaseError, Result},
+    middleware::{
+        client::ClientInfo,
+        impersonation::forbid_impersonation,
+        jwt::{AuthBody, Claims},
+        revocation::revocations,
+        role::Role,
+    },
+    validation::Validator,
+    web::Sensitive,
+};
+
+pub fn router(pool: Pool<Sqlite>) -> Router {
+    Router::new()
+        .route("/token/refresh", routing::post(refresh))
+        .route(
+            "/token/delegate",
+            routing::post(delegate).route_layer(forbid_impersonation(&pool)),
+        )
+        .with_state(pool)
+}
+
+#[derive(Deserialize)]
+struct RefreshPayload {
+    refresh_token: String,
+}
+
+async fn refresh(
+    client: ClientInfo,
+    State(pool): State<Pool<Sqlite>>,
+    Json(payload): Json<RefreshPayload>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle post /token/refresh", "Handler");
+
+    let token_hash = token::hash(&payload.refresh_token);
+    let now = Utc::now().timestamp();
+
+    let stored = sqlx::query!(
+        r#"
+        select family, username, expires_at, revoked_at
+        from refresh_tokens
+        where token_hash = ?
+        "#,
+        token_hash,
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?
+    .ok_or(AuthError::InvalidRefreshToken)?;
+
+    if stored.revoked_at.is_some() || stored.expires_at <= now {
+        return Err(AuthError::InvalidRefreshToken.into());
+    }
+
+    // Every refresh token can be exchanged exactly once. Seeing it a second
+    // time means it leaked, so the whole chain it belongs to is revoked,
+    // along with the access tokens issued from it.
+    let result = sqlx::query!(
+        r#"
+        update refresh_tokens
+        set used_at = ?
+        where token_hash = ?
+        and used_at is null
+        "#,
+        now,
+        token_hash,
+    )
+    .execute(&pool)
+    .await
+    .map_err(|_| DatabaseError::UpdateFailed)?;
+
+    if result.rows_affected() == 0 {
+        warn!(
+            "[{:^12}] ┃ refresh token reused, revoking family of {}",
+            "Handler", stored.username
+        );
+
+        revoke_family(&pool, &stored.family).await?;
+
+        Entry::new(Event::TokenReused, &client)
+            .actor(&stored.username)
+            .details(json!({ "family": stored.family }))
+            .record(&pool)
+            .await;
+
+        return Err(AuthError::InvalidRefreshToken.into());
+    }
+
+    let body = issue(&pool, &stored.username, Some(stored.family.clone())).await?;
+
+    Entry::new(Event::TokenRefreshed, &client)
+        .actor(&stored.username)
+        .details(json!({ "family": stored.family }))
+        .record(&pool)
+        .await;
+
+    Ok((StatusCode::OK, Extension(Sensitive), Json(body)))
+}
+
+#[derive(Deserialize)]
+struct DelegatePayload {
+    #[serde(default)]
+    scopes: Vec<String>,
+    /// Seconds, at most the lifetime of a normal access token.
+    expires_in: Option<i64>,
+}
+
+/// Issues an access token for handing to another program, limited to some of
+/// the scopes of the calling token. It comes without a refresh token.
+async fn delegate(
+    claims: Claims,
+    Json(payload): Json<DelegatePayload>,
+) -> Result<impl IntoResponse> {
+    info!("[{:^12}] ┃ handle post /token/delegate", "Handler");
+
+    Validator::new()
+        .scopes("scopes", &payload.scopes, &claims)
+        .positive("expires_in", payload.expires_in)
+        .finish()?;
+
+    let access_ttl = config().token.access_ttl;
+    let expires_in = payload.expires_in.unwrap_or(access_ttl).min(access_ttl);
+    let now = Utc::now();
+    let scope = claims.narrow_scope(&payload.scopes);
+    let iat = revocations().issued_at(&claims.sub);
+
+    let delegated = Claims {
+        sub: claims.sub,
+        exp: (now + Duration::seconds(expires_in)).timestamp() as usize,
+        iat: iat as usize,
+        jti: token::generate(),
+        role: claims.role,
+        sid: None,
+        scope: scope.clone(),
+        act: claims.act,
+    };
+
+    let access_token = keys().encode(&delegated)?;
+
+    Ok((
+        StatusCode::OK,
+        Extension(Sensitive),
+        Json(json!({
+            "access_token": access_token,
+            "token_type": "Bearer",
+            "expires_in": expires_in,
+            "scope": scope,
+        })),
+    ))
+}
+
+/// Issues an access token and a refresh token for `username`. A login starts
+/// a new token family, refreshing continues the family of the used token.
+pub async fn issue(
+    pool: &Pool<Sqlite>,
+    username: &str,
+    family: Option<String>,
+) -> Result<AuthBody> {
+    let ttl = &config().token;
+    let now = Utc::now();
+
+    let family = family.unwrap_or_else(token::generate);
+
+    let user = sqlx::query!(
+        r#"
+        select role as "role: Role"
+        from users
+        where username = ?
+        "#,
+        username,
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|_| DatabaseError::SelectFailed)?;
+
+    let claims = Claims {
+        sub: username.to_string(),
+        exp: (now + Duration::seconds(ttl.access_ttl)).timestamp() as usize,
+        iat: revocations().issued_at(username) as usize,
+        jti: token::generate(),
+        role: user.role,
+        sid: Some(family.clone()),
+        scope: None,
+        act: None,
+    };
+
+    let access_token = keys().encode(&claims)?;
+
+    let refresh_token = token::generate();
+    let token_hash = token::hash(&refresh_token);
+    let created_at = now.timestamp();
+    let expires_at = created_at + ttl.refresh_ttl;
+
+    sqlx::query!(
+        r#"
+        insert into refresh_tokens (token_hash, family, username, created_at, expires_at)
+        values (?, ?, ?, ?, ?)
+        "#,
+        token_hash,
+        family,
+        username,
+        created_at,
+        expires_at,
+    )
+    .execute(pool)
+    .await
+    .map_err(|_| DatabaseError::InsertFailed)?;
+
+    Ok(AuthBody::new(access_token, ttl.access_ttl, refresh_token))
+}
+
+pub async fn revoke_family(pool: &Pool<Sqlite>, family: &str) -> Result<()> {
+    let now = Utc::now().timestamp();
+
+    sqlx::query!(
+        r#"
+        update refresh_tokens
+        set revoked_at = ?
+        where family = ?
+        and revoked_at is null
+        "#,
+        now,
+        family,
+    )
+    .execute(pool)
+    .await
+    .map_err(|_| DatabaseError::UpdateFailed)?;
+
+    revocations().revoke_family(family);
+
+    Ok(())
+}
+
+pub async fn revoke_user(pool: &Pool<Sqlite>, username: &str) -> Result<()> {
+    let now = Utc::now().timestamp();
+
+    sqlx::query!(
+        r#"
+        update refresh_tokens
+        set revoked_at = ?
+        where username = ?
+        and revoked_at is null
+        "#,
+        now,
+        username,
+    )
+    .execute(pool)
+    .await
+    .map_err(|_| DatabaseError::UpdateFailed)?;
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use std::ops::Deref;
 
-use reqwest::{Response, header};
+use reqwest::{Response, StatusCode, header};
 use serde::Deserialize;
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
@@ -16,11 +16,15 @@ mod test {
 
         register(&client).await?;
         login(&mut client).await?;
-        list(&client).await?;
+        refresh(&mut client).await?;
+        list(&client, StatusCode::OK).await?;
         create(&client).await?;
-        list(&client).await?;
+        list(&client, StatusCode::OK).await?;
         delete(&client).await?;
-        list(&client).await?;
+        list(&client, StatusCode::OK).await?;
+        logout(&client).await?;
+        // The access token is revoked along with the session.
+        list(&client, StatusCode::UNAUTHORIZED).await?;
 
         Ok(())
     }
@@ -40,12 +44,15 @@ mod test {
                 client
                     .post("http://127.0.0.1:3000/register")
                     .header("Content-Type", "application/json")
-                    .body(r#"{"username":"william","password":"040720"}"#),
+                    .body(r#"{"username":"william","password":"correct-horse-battery"}"#),
             )
             .await?;
 
+        let status = response.status();
+
         println!("\n\n=== Response for post {} ===", response.url());
         print(client, response).await?;
+        assert_eq!(status, StatusCode::CREATED);
 
         Ok(())
     }
@@ -56,30 +63,61 @@ mod test {
                 client
                     .post("http://127.0.0.1:3000/login")
                     .header("Content-Type", "application/json")
-                    .body(r#"{"username":"william","password":"040720"}"#),
+                    .body(r#"{"username":"william","password":"correct-horse-battery"}"#),
             )
             .await?;
 
+        let status = response.status();
+
         let headers = response.headers().clone();
 
         println!("\n\n=== Response for POST {} ===", response.url());
         let body = print(client, response).await?;
+        assert_eq!(status, StatusCode::OK);
 
         let body = serde_json::from_str::<AuthBody>(&body)?;
 
         client.cookies(headers.get("set-cookie").unwrap().to_str()?.into());
         client.access_token(body.access_token);
+        client.refresh_token(body.refresh_token);
 
         Ok(())
     }
 
-    async fn list(client: &Client) -> Result<()> {
+    async fn refresh(client: &mut Client) -> Result<()> {
+        let response = client
+            .send(
+                client
+                    .post("http://127.0.0.1:3000/token/refresh")
+                    .header("Content-Type", "application/json")
+                    .body(format!(r#"{{"refresh_token":"{}"}}"#, client.refresh_token)),
+            )
+            .await?;
+
+        let status = response.status();
+
+        println!("\n\n=== Response for POST {} ===", response.url());
+        let body = print(client, response).await?;
+        assert_eq!(status, StatusCode::OK);
+
+        let body = serde_json::from_str::<AuthBody>(&body)?;
+
+        client.access_token(body.access_token);
+        client.refresh_token(body.refresh_token);
+
+        Ok(())
+    }
+
+    async fn list(client: &Client, expected: StatusCode) -> Result<()> {
         let response = client
             .send(client.get("http://127.0.0.1:3000/ticket"))
             .await?;
 
+        let status = response.status();
+
         println!("\n\n=== Response for GET {} ===", response.url());
         print(client, response).await?;
+        assert_eq!(status, expected);
 
         Ok(())
     }
@@ -94,8 +132,11 @@ mod test {
             )
             .await?;
 
+        let status = response.status();
+
         println!("\n\n=== Response for POST {} ===", response.url());
         print(client, response).await?;
+        assert_eq!(status, StatusCode::CREATED);
 
         Ok(())
     }
@@ -105,8 +146,25 @@ mod test {
             .send(client.delete("http://127.0.0.1:3000/ticket?id=1"))
             .await?;
 
+        let status = response.status();
+
         println!("\n\n=== Response for POST {} ===", response.url());
         print(client, response).await?;
+        assert_eq!(status, StatusCode::OK);
+
+        Ok(())
+    }
+
+    async fn logout(client: &Client) -> Result<()> {
+        let response = client
+            .send(client.post("http://127.0.0.1:3000/logout"))
+            .await?;
+
+        let status = response.status();
+
+        println!("\n\n=== Response for POST {} ===", response.url());
+        print(client, response).await?;
+        assert_eq!(status, StatusCode::OK);
 
         Ok(())
     }
@@ -130,14 +188,14 @@ mod test {
         let content_type = headers
             .get(header::CONTENT_TYPE)
             .and_then(|v| v.to_str().ok());
-        let formatted_body = content_type
-            .is_some_and(|content_type| content_type.contains("application/json"))
-            .then(|| {
+        let formatted_body =
+            if content_type.is_some_and(|content_type| content_type.contains("application/json")) {
                 serde_json::from_str::<serde_json::Value>(&body)
                     .and_then(|json| serde_json::to_string_pretty(&json))
                     .unwrap_or(body.clone())
-            })
-            .unwrap_or(body.clone());
+            } else {
+                body.clone()
+            };
 
         println!("=> {:16}:", "Response Body");
         println!("{}", formatted_body);
@@ -151,6 +209,8 @@ mod test {
 struct AuthBody {
     access_token: String,
     token_type: String,
+    expires_in: i64,
+    refresh_token: String,
 }
 
 #[derive(Default)]
@@ -158,6 +218,7 @@ struct Client {
     client: reqwest::Client,
     cookies: String,
     access_token: String,
+    refresh_token: String,
 }
 
 impl Client {
@@ -173,6 +234,10 @@ impl Client {
         self.access_token = access_token;
     }
 
+    fn refresh_token(&mut self, refresh_token: String) {
+        self.refresh_token = refresh_token;
+    }
+
     async fn send(&self, request: reqwest::RequestBuilder) -> Result<Response> {
         request
             .header("Cookie", self.cookies.clone())